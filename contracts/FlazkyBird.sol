@@ -2,14 +2,35 @@
 
 pragma solidity ^0.8.20;
 
+import "@openzeppelin/contracts/access/Ownable.sol";
 import "@openzeppelin/contracts/token/ERC721/extensions/ERC721Enumerable.sol";
 import "@openzeppelin/contracts/utils/Base64.sol";
 import "@openzeppelin/contracts/utils/Strings.sol";
 import "./interfaces/ISP1Verifier.sol";
 
-contract FlazkyBird is ERC721Enumerable {
+contract FlazkyBird is ERC721Enumerable, Ownable {
+    // First word of the public values of an exact score, see `PublicValuesKind` in the logic crate.
+    // Proofs of other kinds (threshold, tournament) can't be added to the leaderboard
+    uint8 public constant SCORE_KIND = 1;
+    // First word of the public values of an exact score whose player is committed as
+    // keccak256(abi.encodePacked(player, salt)): the token is held by the contract until claimed
+    uint8 public constant COMMITTED_SCORE_KIND = 3;
+    // First word of the public values of a segmented proof, followed by the verifying key the segments
+    // were proven with and the public values of the run
+    uint8 public constant AGGREGATE_KIND = 5;
+
     struct PublicValuesStruct {
+        uint8 kind;
         address player;
+        uint64 season;
+        uint256 score;
+        bytes32 nullifier;
+    }
+
+    struct CommittedPublicValuesStruct {
+        uint8 kind;
+        bytes32 playerCommitment;
+        uint64 season;
         uint256 score;
         bytes32 nullifier;
     }
@@ -17,19 +38,39 @@ contract FlazkyBird is ERC721Enumerable {
     struct LeaderboardEntry {
         uint64 score;
         uint64 nextTokenID;
+        uint64 season;
     }
 
     ISP1Verifier public immutable verifier;
     bytes32 public immutable vKey;
+    // `hash_u32` verifying key of the segments aggregated proofs must have verified
+    bytes32 public immutable segmentVKey;
 
     mapping(uint256 tokenId => LeaderboardEntry entry) public leaderboard;
     mapping(bytes32 => bool) public nullifierMap;
 
-    uint256 public leader;
+    // Each season has its own leaderboard, starting at its leader
+    mapping(uint64 season => uint256 tokenId) public leader;
+    // The season is chosen by the prover, only scores proven for this one are accepted
+    uint64 public currentSeason;
+    // Tokens of committed scores, held by the contract until the player reveals the salt
+    mapping(bytes32 playerCommitment => uint256 tokenId) public unclaimed;
+
+    event SeasonStarted(uint64 season);
+    event ScoreClaimed(bytes32 indexed playerCommitment, uint256 indexed tokenId, address recipient);
 
-    constructor(ISP1Verifier _verifier, bytes32 _vKey) ERC721("FlazkyBird", "ZKB") {
+    constructor(ISP1Verifier _verifier, bytes32 _vKey, bytes32 _segmentVKey)
+        ERC721("FlazkyBird", "ZKB")
+        Ownable(msg.sender)
+    {
         verifier = _verifier;
         vKey = _vKey;
+        segmentVKey = _segmentVKey;
+    }
+
+    function setCurrentSeason(uint64 season) external onlyOwner {
+        currentSeason = season;
+        emit SeasonStarted(season);
     }
 
     function addLeaderboardEntry(bytes calldata _publicValues, bytes calldata _proofBytes, uint256 _previousTokenID)
@@ -38,17 +79,62 @@ contract FlazkyBird is ERC721Enumerable {
         // Verifier trace of flazkybird
         verifier.verifyProof(vKey, _publicValues, _proofBytes);
 
-        // Decode public values
-        PublicValuesStruct memory publicValues = abi.decode(_publicValues, (PublicValuesStruct));
+        // Aggregated proofs wrap the public values of the run
+        bytes calldata runPublicValues = _publicValues;
+        if (abi.decode(_publicValues[:32], (uint256)) == AGGREGATE_KIND) {
+            (, bytes32 aggregateSegmentVKey) = abi.decode(_publicValues[:64], (uint8, bytes32));
+            require(aggregateSegmentVKey == segmentVKey, "segments proven with another verifying key");
+            runPublicValues = _publicValues[64:];
+        }
 
-        // check nullifier:
-        require(!nullifierMap[publicValues.nullifier], "nullifier already used");
+        // Decode public values, the player of a committed score is only known once it's claimed
+        uint8 kind = uint8(abi.decode(runPublicValues[:32], (uint256)));
+        if (kind == COMMITTED_SCORE_KIND) {
+            CommittedPublicValuesStruct memory committedValues =
+                abi.decode(runPublicValues, (CommittedPublicValuesStruct));
+            require(unclaimed[committedValues.playerCommitment] == 0, "commitment has an unclaimed score");
+            unclaimed[committedValues.playerCommitment] = _addEntry(
+                address(this),
+                committedValues.season,
+                committedValues.score,
+                committedValues.nullifier,
+                _previousTokenID
+            );
+        } else {
+            PublicValuesStruct memory publicValues = abi.decode(runPublicValues, (PublicValuesStruct));
+            require(publicValues.kind == SCORE_KIND, "public values aren't an exact score");
+            _addEntry(publicValues.player, publicValues.season, publicValues.score, publicValues.nullifier, _previousTokenID);
+        }
+    }
+
+    // Reveals the salt of a committed score, the caller being the committed player, and sends its token to
+    // `recipient`, which doesn't have to be linked to the player
+    function claim(bytes32 salt, address recipient) external {
+        bytes32 playerCommitment = keccak256(abi.encodePacked(msg.sender, salt));
+        uint256 tokenId = unclaimed[playerCommitment];
+        require(tokenId != 0, "no unclaimed score for this commitment");
+        delete unclaimed[playerCommitment];
+        _safeTransfer(address(this), recipient, tokenId);
+        emit ScoreClaimed(playerCommitment, tokenId, recipient);
+    }
 
-        uint256 score = publicValues.score;
+    function _addEntry(address player, uint64 season, uint256 score, bytes32 nullifier, uint256 _previousTokenID)
+        internal
+        returns (uint64 currentTokenID)
+    {
+        require(season == currentSeason, "score proven for another season");
+
+        // check nullifier:
+        require(!nullifierMap[nullifier], "nullifier already used");
 
         // First tokenID is 1
-        uint64 currentTokenID = uint64(totalSupply()) + 1;
-        _safeMint(publicValues.player, currentTokenID);
+        currentTokenID = uint64(totalSupply()) + 1;
+        // the contract can't receive tokens with `_safeMint`, committed scores are minted to it directly
+        if (player == address(this)) {
+            _mint(player, currentTokenID);
+        } else {
+            _safeMint(player, currentTokenID);
+        }
 
         // Search what will be the next token ID
         uint256 previousTokenID = _previousTokenID;
@@ -56,15 +142,16 @@ contract FlazkyBird is ERC721Enumerable {
 
         // claim leadership
         if (_previousTokenID == 0) {
-            if (leader != 0) {
+            if (leader[season] != 0) {
                 // if there's a leader
-                require(score > leaderboard[leader].score, "score should be higher than current lead");
-                nextTokenID = leader;
+                require(score > leaderboard[leader[season]].score, "score should be higher than current lead");
+                nextTokenID = leader[season];
             }
-            leader = currentTokenID;
+            leader[season] = currentTokenID;
         } else {
             // load previous entry
             LeaderboardEntry storage previousEntry = leaderboard[_previousTokenID];
+            require(previousEntry.season == season, "previous entry is from another season");
             require(previousEntry.score >= score, "previous entry has lower score");
 
             if (previousEntry.nextTokenID == 0) {
@@ -87,12 +174,12 @@ contract FlazkyBird is ERC721Enumerable {
         }
 
         // insert score
-        leaderboard[currentTokenID] = LeaderboardEntry(uint64(score), uint64(nextTokenID));
+        leaderboard[currentTokenID] = LeaderboardEntry(uint64(score), uint64(nextTokenID), season);
         // set nullifier
-        nullifierMap[publicValues.nullifier] = true;
+        nullifierMap[nullifier] = true;
     }
 
-    function getLeaderboard(uint256 from, uint256 items)
+    function getLeaderboard(uint64 season, uint256 from, uint256 items)
         public
         view
         returns (LeaderboardEntry[] memory, uint256 nextIndex)
@@ -100,7 +187,7 @@ contract FlazkyBird is ERC721Enumerable {
         LeaderboardEntry[] memory entries = new LeaderboardEntry[](items);
         uint256 next = from;
         if (next == 0) {
-            next = leader;
+            next = leader[season];
         }
         for (uint256 i = 0; i < items; i++) {
             entries[i] = leaderboard[next];
@@ -123,6 +210,8 @@ contract FlazkyBird is ERC721Enumerable {
                             '"description": "NFT obtained playing FlaZKy bird and proving the score on-chain",',
                             '"attributes": [{"trait_type": "score", "key":"score","value": ',
                             Strings.toString(nftData.score),
+                            '}, {"trait_type": "season", "key":"season","value": ',
+                            Strings.toString(nftData.season),
                             "}],",
                             '"image":"https://toppng.com/uploads/preview/flappy-bird-pixel-art-flappy-bird-1156289438531sspmvwnk.png"}'
                         )
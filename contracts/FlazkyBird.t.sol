@@ -10,11 +10,14 @@ import {console} from "forge-std/console.sol";
 contract FlazkyBirdTest is Test {
     FlazkyBird public flazkyBird;
     SP1VerifierMock public mockVerifier;
+    bytes32 constant SEGMENT_VKEY = bytes32(uint256(0x5e9));
+
+    event SeasonStarted(uint64 season);
 
     function setUp() public {
         vm.prank(address(0xf00));
         mockVerifier = new SP1VerifierMock();
-        flazkyBird = new FlazkyBird(mockVerifier, bytes32(type(uint256).max));
+        flazkyBird = new FlazkyBird(mockVerifier, bytes32(type(uint256).max), SEGMENT_VKEY);
     }
 
     function testFlazkyy() public {
@@ -24,7 +27,7 @@ contract FlazkyBirdTest is Test {
 
         bytes memory proofBytes = new bytes(0);
         FlazkyBird.PublicValuesStruct memory publicValues =
-            FlazkyBird.PublicValuesStruct({player: player1, score: 100, nullifier: bytes32(0)});
+            FlazkyBird.PublicValuesStruct({kind: 1, player: player1, season: 0, score: 100, nullifier: bytes32(0)});
 
         flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0); // index 1, [1: 0(100)]
         publicValues.player = player2;
@@ -48,10 +51,10 @@ contract FlazkyBirdTest is Test {
         publicValues.nullifier = bytes32(uint256(5));
         flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0); // index 6, [1: 4(100), 2: 2(200), 3: 1(300), 4 3(200), 5 5(50), 6: 0(1000)]
 
-        (FlazkyBird.LeaderboardEntry[] memory leaderboard,) = flazkyBird.getLeaderboard(0, 6);
+        (FlazkyBird.LeaderboardEntry[] memory leaderboard,) = flazkyBird.getLeaderboard(0, 0, 6);
 
         // #0
-        assertEq(flazkyBird.ownerOf(flazkyBird.leader()), player3);
+        assertEq(flazkyBird.ownerOf(flazkyBird.leader(0)), player3);
         assertEq(leaderboard[0].score, 1000);
         assertEq(leaderboard[0].nextTokenID, 3);
         // #1
@@ -82,4 +85,227 @@ contract FlazkyBirdTest is Test {
         string memory tokenURI = flazkyBird.tokenURI(1);
         console.log(tokenURI);
     }
+
+    function testSeasons() public {
+        bytes memory proofBytes = new bytes(0);
+        FlazkyBird.PublicValuesStruct memory publicValues =
+            FlazkyBird.PublicValuesStruct({kind: 1, player: address(0x1), season: 1, score: 100, nullifier: bytes32(0)});
+        flazkyBird.setCurrentSeason(1);
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0); // season 1, index 1
+
+        // a lower score leads a fresh season
+        flazkyBird.setCurrentSeason(2);
+        publicValues.season = 2;
+        publicValues.score = 10;
+        publicValues.nullifier = bytes32(uint256(1));
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0); // season 2, index 2
+        assertEq(flazkyBird.leader(1), 1);
+        assertEq(flazkyBird.leader(2), 2);
+        assertEq(flazkyBird.leader(0), 0);
+
+        // entries can't be inserted after an entry of another season
+        publicValues.score = 5;
+        publicValues.nullifier = bytes32(uint256(2));
+        vm.expectRevert("previous entry is from another season");
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 1);
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 2); // season 2, index 3
+
+        (FlazkyBird.LeaderboardEntry[] memory season1, uint256 next1) = flazkyBird.getLeaderboard(1, 0, 1);
+        assertEq(season1[0].score, 100);
+        assertEq(next1, 0);
+        (FlazkyBird.LeaderboardEntry[] memory season2, uint256 next2) = flazkyBird.getLeaderboard(2, 0, 2);
+        assertEq(season2[0].score, 10);
+        assertEq(season2[0].nextTokenID, 3);
+        assertEq(season2[1].score, 5);
+        assertEq(season2[1].season, 2);
+        assertEq(next2, 0);
+
+        // nullifiers are shared by all the seasons
+        flazkyBird.setCurrentSeason(3);
+        publicValues.season = 3;
+        vm.expectRevert("nullifier already used");
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0);
+    }
+
+    function testCurrentSeason() public {
+        bytes memory proofBytes = new bytes(0);
+        FlazkyBird.PublicValuesStruct memory publicValues =
+            FlazkyBird.PublicValuesStruct({kind: 1, player: address(0x1), season: 1, score: 100, nullifier: bytes32(0)});
+
+        // the prover picks the season, only the current one is accepted
+        vm.expectRevert("score proven for another season");
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0);
+        assertFalse(flazkyBird.nullifierMap(publicValues.nullifier));
+
+        // only the owner starts a season
+        vm.prank(address(0x1));
+        vm.expectRevert(abi.encodeWithSelector(Ownable.OwnableUnauthorizedAccount.selector, address(0x1)));
+        flazkyBird.setCurrentSeason(1);
+
+        vm.expectEmit(true, true, true, true);
+        emit SeasonStarted(1);
+        flazkyBird.setCurrentSeason(1);
+        assertEq(flazkyBird.currentSeason(), 1);
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0);
+
+        // scores of a past season can't be added anymore
+        flazkyBird.setCurrentSeason(2);
+        publicValues.nullifier = bytes32(uint256(1));
+        vm.expectRevert("score proven for another season");
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0);
+    }
+
+    function testRejectsOtherProofKinds() public {
+        bytes memory proofBytes = new bytes(0);
+        bytes32 nullifier = bytes32(uint256(7));
+
+        // a threshold proof has the same layout as a score, with the threshold as the score
+        bytes memory thresholdValues = abi.encode(uint8(2), address(0x1), uint64(0), uint256(100), nullifier);
+        vm.expectRevert("public values aren't an exact score");
+        flazkyBird.addLeaderboardEntry(thresholdValues, proofBytes, 0);
+        // same for a threshold with a committed player
+        bytes memory committedValues = abi.encode(uint8(4), address(0x1), uint64(0), uint256(100), nullifier);
+        vm.expectRevert("public values aren't an exact score");
+        flazkyBird.addLeaderboardEntry(committedValues, proofBytes, 0);
+        // and for a tournament entry, whose id would be decoded as the season
+        bytes memory tournamentValues =
+            abi.encode(uint8(6), address(0x1), uint64(1), bytes32(uint256(2)), uint256(100), nullifier);
+        vm.expectRevert("public values aren't an exact score");
+        flazkyBird.addLeaderboardEntry(tournamentValues, proofBytes, 0);
+
+        // the nullifier wasn't burned, the exact score of the trace can still be added
+        assertFalse(flazkyBird.nullifierMap(nullifier));
+        FlazkyBird.PublicValuesStruct memory publicValues =
+            FlazkyBird.PublicValuesStruct({kind: 1, player: address(0x1), season: 0, score: 120, nullifier: nullifier});
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0);
+        assertTrue(flazkyBird.nullifierMap(nullifier));
+    }
+
+    function testCommittedScores() public {
+        bytes memory proofBytes = new bytes(0);
+        address player = address(0x1);
+        bytes32 salt = bytes32(uint256(0x5a17));
+        bytes32 commitment = keccak256(abi.encodePacked(player, salt));
+        FlazkyBird.CommittedPublicValuesStruct memory publicValues = FlazkyBird.CommittedPublicValuesStruct({
+            kind: 3,
+            playerCommitment: commitment,
+            season: 0,
+            score: 100,
+            nullifier: bytes32(uint256(1))
+        });
+
+        // the entry is ranked, its token is held by the contract until claimed
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 0);
+        assertEq(flazkyBird.leader(0), 1);
+        assertEq(flazkyBird.ownerOf(1), address(flazkyBird));
+        assertEq(flazkyBird.unclaimed(commitment), 1);
+        assertTrue(flazkyBird.nullifierMap(publicValues.nullifier));
+
+        // a second score can't be committed to the same slot before the first one is claimed
+        publicValues.score = 50;
+        publicValues.nullifier = bytes32(uint256(2));
+        vm.expectRevert("commitment has an unclaimed score");
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 1);
+        assertFalse(flazkyBird.nullifierMap(publicValues.nullifier));
+
+        // the salt alone doesn't claim the score, the caller has to be the committed player
+        vm.prank(address(0x2));
+        vm.expectRevert("no unclaimed score for this commitment");
+        flazkyBird.claim(salt, address(0x2));
+        vm.prank(player);
+        vm.expectRevert("no unclaimed score for this commitment");
+        flazkyBird.claim(bytes32(uint256(0xbad)), player);
+
+        // the player sends the token to a fresh address
+        address recipient = address(0xbeef);
+        vm.prank(player);
+        flazkyBird.claim(salt, recipient);
+        assertEq(flazkyBird.ownerOf(1), recipient);
+        assertEq(flazkyBird.unclaimed(commitment), 0);
+        vm.prank(player);
+        vm.expectRevert("no unclaimed score for this commitment");
+        flazkyBird.claim(salt, recipient);
+
+        // committed scores share the leaderboard and the nullifiers of plain scores
+        FlazkyBird.PublicValuesStruct memory plainValues =
+            FlazkyBird.PublicValuesStruct({kind: 1, player: player, season: 0, score: 200, nullifier: bytes32(uint256(1))});
+        vm.expectRevert("nullifier already used");
+        flazkyBird.addLeaderboardEntry(abi.encode(plainValues), proofBytes, 0);
+        plainValues.nullifier = bytes32(uint256(3));
+        flazkyBird.addLeaderboardEntry(abi.encode(plainValues), proofBytes, 0);
+        (FlazkyBird.LeaderboardEntry[] memory leaderboard,) = flazkyBird.getLeaderboard(0, 0, 2);
+        assertEq(leaderboard[0].score, 200);
+        assertEq(leaderboard[1].score, 100);
+
+        // the slot is free again once claimed
+        flazkyBird.addLeaderboardEntry(abi.encode(publicValues), proofBytes, 1);
+        assertEq(flazkyBird.unclaimed(commitment), 3);
+        assertEq(flazkyBird.ownerOf(3), address(flazkyBird));
+    }
+
+    function testAggregatedProofs() public {
+        bytes memory proofBytes = new bytes(0);
+        FlazkyBird.PublicValuesStruct memory publicValues =
+            FlazkyBird.PublicValuesStruct({kind: 1, player: address(0x1), season: 0, score: 150, nullifier: bytes32(uint256(9))});
+
+        // segments proven with another program are rejected, and the nullifier isn't burned
+        bytes memory otherSegments = bytes.concat(abi.encode(uint8(5), bytes32(uint256(0xbad))), abi.encode(publicValues));
+        vm.expectRevert("segments proven with another verifying key");
+        flazkyBird.addLeaderboardEntry(otherSegments, proofBytes, 0);
+        assertFalse(flazkyBird.nullifierMap(publicValues.nullifier));
+
+        // an aggregation of threshold proofs is still rejected
+        bytes memory threshold = bytes.concat(
+            abi.encode(uint8(5), SEGMENT_VKEY), abi.encode(uint8(2), address(0x1), uint64(0), uint256(150), bytes32(uint256(9)))
+        );
+        vm.expectRevert("public values aren't an exact score");
+        flazkyBird.addLeaderboardEntry(threshold, proofBytes, 0);
+
+        flazkyBird.addLeaderboardEntry(bytes.concat(abi.encode(uint8(5), SEGMENT_VKEY), abi.encode(publicValues)), proofBytes, 0);
+        (uint64 score,,) = flazkyBird.leaderboard(1);
+        assertEq(score, 150);
+        assertEq(flazkyBird.ownerOf(1), address(0x1));
+        assertTrue(flazkyBird.nullifierMap(publicValues.nullifier));
+    }
+
+    // Fixtures generated from real traces by the host `contract-fixtures` command
+    function testFixtures() public {
+        string memory path = string.concat(vm.projectRoot(), "/contracts/fixtures/flazky-fixtures.json");
+        vm.skip(!vm.exists(path));
+        string memory json = vm.readFile(path);
+        FlazkyBird fixtureFlazkyBird = new FlazkyBird(mockVerifier, vm.parseJsonBytes32(json, ".vkey"), bytes32(0));
+        uint64 season = uint64(vm.parseJsonUint(json, ".season"));
+        fixtureFlazkyBird.setCurrentSeason(season);
+
+        uint256 count = vm.parseJsonUint(json, ".count");
+        for (uint256 i = 0; i < count; i++) {
+            string memory entry = string.concat(".entries[", vm.toString(i), "]");
+            fixtureFlazkyBird.addLeaderboardEntry(
+                vm.parseJsonBytes(json, string.concat(entry, ".publicValues")),
+                vm.parseJsonBytes(json, string.concat(entry, ".proof")),
+                vm.parseJsonUint(json, string.concat(entry, ".previousTokenId"))
+            );
+            // token IDs start at 1
+            assertEq(fixtureFlazkyBird.ownerOf(i + 1), vm.parseJsonAddress(json, string.concat(entry, ".player")));
+            (uint64 score,, uint64 entrySeason) = fixtureFlazkyBird.leaderboard(i + 1);
+            assertEq(score, vm.parseJsonUint(json, string.concat(entry, ".score")));
+            assertEq(entrySeason, season);
+            assertTrue(fixtureFlazkyBird.nullifierMap(vm.parseJsonBytes32(json, string.concat(entry, ".nullifier"))));
+        }
+
+        // every entry is in the leaderboard, sorted by score
+        (FlazkyBird.LeaderboardEntry[] memory leaderboard, uint256 next) = fixtureFlazkyBird.getLeaderboard(season, 0, count);
+        assertEq(next, 0);
+        for (uint256 i = 1; i < count; i++) {
+            assert(leaderboard[i].score <= leaderboard[i - 1].score);
+        }
+
+        // same trace proven for another player
+        vm.expectRevert("nullifier already used");
+        fixtureFlazkyBird.addLeaderboardEntry(
+            vm.parseJsonBytes(json, ".duplicate.publicValues"),
+            vm.parseJsonBytes(json, ".duplicate.proof"),
+            vm.parseJsonUint(json, ".duplicate.previousTokenId")
+        );
+    }
 }
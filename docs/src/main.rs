@@ -1,7 +1,7 @@
 use bevy::math::UVec2;
 use bevy::prelude::*;
 use bevy_asset::AssetMetaCheck;
-use flazky_bird_lib::FlazkyBird;
+use flazky_bird_lib::{Action, FlazkyBird, TraceItem};
 use rand::Rng;
 // use bincode;
 // use std::fs::File;
@@ -12,6 +12,18 @@ use wasm_bindgen::prelude::*;
 #[derive(Component)]
 struct GameLogic {
     flazky_bird: FlazkyBird,
+    /// Sum of the physics deltas of the current play
+    elapsed: f32,
+}
+
+/// Replays the best run, in prover mode, alongside the current play
+#[derive(Component)]
+struct Ghost {
+    flazky_bird: FlazkyBird,
+    trace: Vec<TraceItem>,
+    next: usize,
+    /// Sum of the physics deltas replayed so far
+    elapsed: f32,
 }
 
 #[derive(Component, Deref, DerefMut)]
@@ -43,6 +55,12 @@ struct Score {
     value: u32,
 }
 
+/// When set, every play uses this seed, the one of the best run, and races its ghost
+#[derive(Resource, Default)]
+struct SeedMode {
+    fixed_seed: Option<i32>,
+}
+
 #[derive(Resource, Default)]
 struct GameState {
     game_over: bool,
@@ -67,6 +85,9 @@ struct AnimationTimer(Timer);
 #[derive(Event)]
 struct GameOverEvent();
 
+#[derive(Event)]
+struct NewPlayEvent(i32);
+
 const WINDOW_Y: f32 = 512.;
 const WINDOW_X: f32 = 800.;
 
@@ -76,6 +97,7 @@ fn main() {
     App::new()
         .init_resource::<Score>()
         .init_resource::<GameState>()
+        .init_resource::<SeedMode>()
         .add_systems(Startup, setup)
         .add_systems(Update, display_score)
         .add_systems(Update, jump)
@@ -87,6 +109,10 @@ fn main() {
         .add_systems(Update, move_pipes_and_game_logic.run_if(game_is_active))
         .add_event::<GameOverEvent>()
         .add_systems(Update, game_over_event)
+        .add_event::<NewPlayEvent>()
+        .add_systems(Update, start_ghost)
+        .add_systems(Update, move_ghost.run_if(game_is_active))
+        .add_systems(Update, toggle_fixed_seed.run_if(game_is_not_active))
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
@@ -204,12 +230,32 @@ fn setup(
     commands.spawn((
         GameLogic {
             flazky_bird: FlazkyBird::new(false),
+            elapsed: 0.,
         },
         GameLogicTimerPhysics(Timer::from_seconds(0.03, TimerMode::Repeating)), // ~30 fps
         GameLogicTimerJump(Timer::from_seconds(0.03, TimerMode::Repeating)),    // ~30 fps
         GameLogicTimerCollisions(Timer::from_seconds(0.03, TimerMode::Repeating)), // ~30 fps
     ));
 
+    // between the pipes and the bird, hidden until a ghost run starts
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: bird.clone(),
+            atlas: TextureAtlas {
+                layout: texture_atlas_layout.clone(),
+                index: 1,
+            },
+            sprite: Sprite {
+                color: Color::srgba(1., 1., 1., 0.4),
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 0., 3.5),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Ghost::new(),
+    ));
+
     commands.spawn((
         SpriteSheetBundle {
             texture: bird,
@@ -311,9 +357,11 @@ fn physics(
         return;
     }
     let mut bird = bird_query.single_mut();
-    if gl.flazky_bird.apply_physics(0.03 + timer.elapsed_secs()) {
+    let delta_seconds = 0.03 + timer.elapsed_secs();
+    if gl.flazky_bird.apply_physics(delta_seconds) {
         ev_game_over.send(GameOverEvent());
     }
+    gl.elapsed += delta_seconds;
     let bird_position = gl.flazky_bird.bird_position();
     bird.translation.y = bird_position.y as f32;
     // bird.rotation = bird_position.rotation;
@@ -403,6 +451,8 @@ fn jump(
         &mut Visibility,
         Or<(With<GameOverDisplay>, With<PressSpace>)>,
     >,
+    seed_mode: Res<SeedMode>,
+    mut ev_new_play: EventWriter<NewPlayEvent>,
 ) {
     let mut gl = game_logic_query.single_mut();
     if input.just_pressed(KeyCode::Space) {
@@ -418,14 +468,15 @@ fn jump(
                 ..default()
             });
         } else {
+            let seed = seed_mode
+                .fixed_seed
+                .unwrap_or_else(|| rand::thread_rng().gen_range(-20000000..75000000));
             if game_over.first_start {
                 game_over.first_start = false;
-                let mut rng = rand::thread_rng();
-                gl.flazky_bird.new_play(rng.gen_range(-20000000..75000000));
+                gl.flazky_bird.new_play(seed);
             } else {
                 let mut bird = bird_query.single_mut();
-                let mut rng = rand::thread_rng();
-                gl.flazky_bird.new_play(rng.gen_range(-20000000..75000000));
+                gl.flazky_bird.new_play(seed);
                 let bird_position = gl.flazky_bird.bird_position();
                 bird.translation.y = bird_position.y as f32;
                 // bird.rotation = bird_position.rotation;
@@ -436,6 +487,9 @@ fn jump(
                 }
             }
 
+            gl.elapsed = 0.;
+            ev_new_play.send(NewPlayEvent(seed));
+
             for mut vis in game_over_and_space_query.iter_mut() {
                 *vis = Visibility::Hidden;
             }
@@ -444,6 +498,124 @@ fn jump(
     }
 }
 
+impl Ghost {
+    fn new() -> Self {
+        Self {
+            flazky_bird: FlazkyBird::new(true),
+            trace: Vec::new(),
+            next: 0,
+            elapsed: 0.,
+        }
+    }
+
+    fn start(&mut self, trace: Vec<TraceItem>, seed: i32) {
+        // a fresh game, in prover mode the game is never marked as over
+        self.flazky_bird = FlazkyBird::new(true);
+        self.flazky_bird.new_play(seed);
+        self.trace = trace;
+        self.next = 1;
+        self.elapsed = 0.;
+    }
+
+    fn stop(&mut self) {
+        self.trace.clear();
+        self.next = 0;
+    }
+
+    /// Replays the trace up to `elapsed`, the time of the current play. Returns false once the
+    /// whole trace was replayed
+    fn advance(&mut self, elapsed: f32) -> bool {
+        while let Some(item) = self.trace.get(self.next) {
+            let delta_seconds = f32::from_le_bytes(item.data);
+            match item.action {
+                Action::ApplyPhysics => {
+                    if self.elapsed + delta_seconds > elapsed {
+                        return true;
+                    }
+                    self.elapsed += delta_seconds;
+                    self.flazky_bird.apply_physics(delta_seconds);
+                }
+                Action::CheckCollisionAndMovePipes => {
+                    self.flazky_bird.check_collision_and_move_pipes(delta_seconds);
+                }
+                Action::Jump => self.flazky_bird.jump(),
+                Action::NewPlay | Action::GameOver => {}
+            }
+            self.next += 1;
+        }
+        false
+    }
+}
+
+fn toggle_fixed_seed(
+    input: Res<ButtonInput<KeyCode>>,
+    mut seed_mode: ResMut<SeedMode>,
+    game_logic_query: Query<&GameLogic>,
+) {
+    if !input.just_pressed(KeyCode::KeyF) {
+        return;
+    }
+    if seed_mode.fixed_seed.take().is_some() {
+        alert("Fixed seed off: every play gets a new seed");
+        return;
+    }
+    let gl = game_logic_query.single();
+    match gl.flazky_bird.get_high_score_treacer().first() {
+        Some(item) if item.action == Action::NewPlay => {
+            seed_mode.fixed_seed = Some(i32::from_le_bytes(item.data));
+            alert(
+                format!(
+                    "Fixed seed on: race the ghost of your best run ({} points)",
+                    gl.flazky_bird.get_high_score()
+                )
+                .as_str(),
+            );
+        }
+        _ => alert("Set a high score first, the ghost replays it on the same seed"),
+    }
+}
+
+fn start_ghost(
+    seed_mode: Res<SeedMode>,
+    mut ev_new_play: EventReader<NewPlayEvent>,
+    game_logic_query: Query<&GameLogic>,
+    mut ghost_query: Query<(&mut Ghost, &mut Transform, &mut Visibility)>,
+) {
+    for NewPlayEvent(seed) in ev_new_play.read() {
+        let (mut ghost, mut transform, mut vis) = ghost_query.single_mut();
+        let trace = game_logic_query.single().flazky_bird.get_high_score_treacer();
+        // a ghost only makes sense on the seed it was played with
+        let same_seed = matches!(
+            trace.first(),
+            Some(item) if item.action == Action::NewPlay && i32::from_le_bytes(item.data) == *seed
+        );
+        if seed_mode.fixed_seed.is_some() && same_seed {
+            ghost.start(trace, *seed);
+            transform.translation.y = ghost.flazky_bird.bird_position().y as f32;
+            *vis = Visibility::Visible;
+        } else {
+            ghost.stop();
+            *vis = Visibility::Hidden;
+        }
+    }
+}
+
+fn move_ghost(
+    game_logic_query: Query<&GameLogic>,
+    mut ghost_query: Query<(&mut Ghost, &mut Transform, &mut Visibility)>,
+) {
+    let (mut ghost, mut transform, mut vis) = ghost_query.single_mut();
+    if matches!(*vis, Visibility::Hidden) {
+        return;
+    }
+    if !ghost.advance(game_logic_query.single().elapsed) {
+        // the best run is over
+        *vis = Visibility::Hidden;
+        return;
+    }
+    transform.translation.y = ghost.flazky_bird.bird_position().y as f32;
+}
+
 fn move_bg(time: Res<Time>, mut bg_query: Query<&mut Transform, With<Background>>) {
     let delta_seconds = time.delta_seconds();
     for mut transform in bg_query.iter_mut() {
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Action, TraceItem};
+
+/// Maximum number of items a compact trace may expand to, checked before expanding untrusted bytes
+pub const MAX_COMPACT_ITEMS: u64 = 1 << 24;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompactError {
+    MissingNewPlay,
+    /// The item can't be represented, like a `NewPlay` after the first item or a `Jump` with data
+    Unsupported { index: usize },
+    /// The compact trace doesn't decode back to the original items
+    NotLossless,
+    /// The compact trace expands to more than `MAX_COMPACT_ITEMS` items
+    TooLong { items: u64 },
+    Encoding(String),
+}
+
+impl fmt::Display for CompactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompactError::MissingNewPlay => write!(f, "trace doesn't start with NewPlay"),
+            CompactError::Unsupported { index } => write!(f, "item {} can't be represented in a compact trace", index),
+            CompactError::NotLossless => write!(f, "compact trace doesn't match the original trace"),
+            CompactError::TooLong { items } => {
+                write!(f, "compact trace expands to {} items, more than {}", items, MAX_COMPACT_ITEMS)
+            }
+            CompactError::Encoding(err) => write!(f, "invalid compact trace: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for CompactError {}
+
+/// Compact representation of a trace.
+///
+/// Every frame of the game produces an `ApplyPhysics` and a `CheckCollisionAndMovePipes` item, with
+/// mostly the same delta. Each of those items is a tick: ticks alternate between both actions
+/// (starting with `ApplyPhysics`), so only the ticks that break the alternation are stored, the jumps
+/// are a bitmap over the ticks, and deltas are only stored when they change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactTrace {
+    pub seed: i32,
+    /// Number of `ApplyPhysics` and `CheckCollisionAndMovePipes` items
+    pub ticks: u32,
+    /// Bit `i` is set when tick `i` is preceded by a jump. Bit `ticks` stands for the jumps after the last tick
+    pub jumps: Vec<u8>,
+    /// `(tick, count)` of the extra jumps, when a tick is preceded by more than one
+    pub extra_jumps: Vec<(u32, u32)>,
+    /// Ticks that break the `ApplyPhysics`/`CheckCollisionAndMovePipes` alternation, in order
+    pub swapped: Vec<u32>,
+    /// `(tick, delta)` every time the delta of `ApplyPhysics` changes
+    pub physics_deltas: Vec<(u32, f32)>,
+    /// `(tick, delta)` every time the delta of `CheckCollisionAndMovePipes` changes
+    pub collision_deltas: Vec<(u32, f32)>,
+    pub game_over: bool,
+}
+
+/// Whether the ticks are strictly increasing and all below `end`
+fn increasing(ticks: impl IntoIterator<Item = u32>, end: u64) -> bool {
+    let mut previous = None;
+    ticks.into_iter().all(|tick| {
+        let ok = (tick as u64) < end && previous < Some(tick);
+        previous = Some(tick);
+        ok
+    })
+}
+
+impl CompactTrace {
+    /// Builds a compact trace, failing if it can't be converted back to exactly the same items
+    pub fn from_items(items: &[TraceItem]) -> Result<Self, CompactError> {
+        let seed = match items.first() {
+            Some(TraceItem { action: Action::NewPlay, data }) => i32::from_le_bytes(*data),
+            _ => return Err(CompactError::MissingNewPlay),
+        };
+        let mut compact = CompactTrace {
+            seed,
+            ticks: 0,
+            jumps: Vec::new(),
+            extra_jumps: Vec::new(),
+            swapped: Vec::new(),
+            physics_deltas: Vec::new(),
+            collision_deltas: Vec::new(),
+            game_over: false,
+        };
+        let mut pending_jumps = 0;
+        let mut last_physics_delta = None;
+        let mut last_collision_delta = None;
+        for (index, item) in items.iter().enumerate().skip(1) {
+            match item.action {
+                Action::Jump => {
+                    if item.data != [0; 4] {
+                        return Err(CompactError::Unsupported { index });
+                    }
+                    pending_jumps += 1;
+                }
+                Action::ApplyPhysics | Action::CheckCollisionAndMovePipes => {
+                    let tick = compact.ticks;
+                    compact.push_jumps(tick, pending_jumps);
+                    pending_jumps = 0;
+                    let is_physics = item.action == Action::ApplyPhysics;
+                    if is_physics != tick.is_multiple_of(2) {
+                        compact.swapped.push(tick);
+                    }
+                    let (deltas, last_delta) = if is_physics {
+                        (&mut compact.physics_deltas, &mut last_physics_delta)
+                    } else {
+                        (&mut compact.collision_deltas, &mut last_collision_delta)
+                    };
+                    // compare the bits, so every delta (even NaN) round-trips
+                    let bits = u32::from_le_bytes(item.data);
+                    if *last_delta != Some(bits) {
+                        deltas.push((tick, f32::from_bits(bits)));
+                        *last_delta = Some(bits);
+                    }
+                    compact.ticks += 1;
+                }
+                Action::GameOver => {
+                    if index != items.len() - 1 || item.data != [0; 4] {
+                        return Err(CompactError::Unsupported { index });
+                    }
+                    compact.game_over = true;
+                }
+                Action::NewPlay => return Err(CompactError::Unsupported { index }),
+            }
+        }
+        compact.push_jumps(compact.ticks, pending_jumps);
+
+        if compact.to_items() != items {
+            return Err(CompactError::NotLossless);
+        }
+        Ok(compact)
+    }
+
+    fn push_jumps(&mut self, tick: u32, count: u32) {
+        let byte = tick as usize / 8;
+        if self.jumps.len() <= byte {
+            self.jumps.resize(byte + 1, 0);
+        }
+        if count > 0 {
+            self.jumps[byte] |= 1 << (tick % 8);
+        }
+        if count > 1 {
+            self.extra_jumps.push((tick, count - 1));
+        }
+    }
+
+    /// Whether tick `tick` is an `ApplyPhysics`, `swapped` being sorted
+    fn is_physics(&self, tick: u32) -> bool {
+        tick.is_multiple_of(2) != self.swapped.binary_search(&tick).is_ok()
+    }
+
+    fn jumped(&self, tick: u32) -> bool {
+        self.jumps.get(tick as usize / 8).is_some_and(|byte| byte & (1 << (tick % 8)) != 0)
+    }
+
+    /// Checks the invariants `to_items` relies on, and that the expanded trace stays bounded
+    fn validate(&self) -> Result<(), CompactError> {
+        let invalid = |err: &str| Err(CompactError::Encoding(err.to_string()));
+        if self.jumps.len() != self.ticks as usize / 8 + 1 {
+            return invalid("jump bitmap doesn't match the number of ticks");
+        }
+        let ticks = self.ticks as u64;
+        if !increasing(self.swapped.iter().copied(), ticks)
+            || !increasing(self.physics_deltas.iter().map(|(tick, _)| *tick), ticks)
+            || !increasing(self.collision_deltas.iter().map(|(tick, _)| *tick), ticks)
+            || !increasing(self.extra_jumps.iter().map(|(tick, _)| *tick), ticks + 1)
+        {
+            return invalid("ticks out of order or out of range");
+        }
+        // `to_items` would skip a delta stored on a tick of the other action
+        if self.physics_deltas.iter().any(|(tick, _)| !self.is_physics(*tick))
+            || self.collision_deltas.iter().any(|(tick, _)| self.is_physics(*tick))
+        {
+            return invalid("delta on a tick of the other action");
+        }
+        if self.extra_jumps.iter().any(|(tick, count)| *count == 0 || !self.jumped(*tick)) {
+            return invalid("extra jumps on a tick without a jump");
+        }
+        let jumps: u64 = self.jumps.iter().map(|byte| byte.count_ones() as u64).sum();
+        let extra_jumps: u64 = self.extra_jumps.iter().map(|(_, count)| *count as u64).sum();
+        let items = 1 + self.ticks as u64 + jumps + extra_jumps + self.game_over as u64;
+        if items > MAX_COMPACT_ITEMS {
+            return Err(CompactError::TooLong { items });
+        }
+        Ok(())
+    }
+
+    /// Expands the compact trace back to the items it was built from
+    pub fn to_items(&self) -> Vec<TraceItem> {
+        let mut items = vec![TraceItem { action: Action::NewPlay, data: self.seed.to_le_bytes() }];
+        let mut swapped = self.swapped.iter().peekable();
+        let mut physics_deltas = self.physics_deltas.iter().peekable();
+        let mut collision_deltas = self.collision_deltas.iter().peekable();
+        let mut extra_jumps = self.extra_jumps.iter().peekable();
+        let mut physics_delta = 0f32;
+        let mut collision_delta = 0f32;
+        for tick in 0..=self.ticks {
+            let jumps = if self.jumped(tick) {
+                1 + extra_jumps.next_if(|(extra_tick, _)| *extra_tick == tick).map_or(0, |(_, count)| *count)
+            } else {
+                0
+            };
+            for _ in 0..jumps {
+                items.push(TraceItem { action: Action::Jump, data: [0; 4] });
+            }
+            if tick == self.ticks {
+                break;
+            }
+            let mut is_physics = tick.is_multiple_of(2);
+            if swapped.peek() == Some(&&tick) {
+                swapped.next();
+                is_physics = !is_physics;
+            }
+            let (action, delta) = if is_physics {
+                if let Some((_, delta)) = physics_deltas.next_if(|(change_tick, _)| *change_tick == tick) {
+                    physics_delta = *delta;
+                }
+                (Action::ApplyPhysics, physics_delta)
+            } else {
+                if let Some((_, delta)) = collision_deltas.next_if(|(change_tick, _)| *change_tick == tick) {
+                    collision_delta = *delta;
+                }
+                (Action::CheckCollisionAndMovePipes, collision_delta)
+            };
+            items.push(TraceItem { action, data: delta.to_le_bytes() });
+        }
+        if self.game_over {
+            items.push(TraceItem { action: Action::GameOver, data: [0; 4] });
+        }
+        items
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+
+    /// Decodes a compact trace, rejecting one that is malformed or expands past `MAX_COMPACT_ITEMS`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CompactError> {
+        let compact: Self = bincode::deserialize(bytes).map_err(|err| CompactError::Encoding(err.to_string()))?;
+        compact.validate()?;
+        Ok(compact)
+    }
+}
@@ -1,20 +1,221 @@
-use alloy_primitives::{Address};
+use alloy_primitives::{keccak256, Address, B256};
+use alloy_sol_types::{sol, SolType};
 use serde::{Serialize, Deserialize};
 
+mod compact;
+mod tournament;
+mod trace;
+mod typed;
+
+pub use compact::{CompactError, CompactTrace, MAX_COMPACT_ITEMS};
+pub use tournament::{Tournament, TournamentError, TournamentResult};
+pub use trace::{decode_item, replay_encoded, replay_encoded_with, Replay, ReplayHook, TraceDecoder, TraceError};
+pub use typed::{TypedTraceItem, UnexpectedData};
+
 const GRAVITY: f32 = -30.;
-const WINDOW_Y: i32 = 512;
-const BIRD_WIDTH: f32 = 20.;
-const BIRD_HEIGHT: i32 = 32;
-const PIPE_WIDTH: f32 = 48.;
-const PIPE_HEIGHT: i32 = 316;
+pub const WINDOW_Y: i32 = 512;
+pub const BIRD_WIDTH: f32 = 20.;
+pub const BIRD_HEIGHT: i32 = 32;
+pub const PIPE_WIDTH: f32 = 48.;
+pub const PIPE_HEIGHT: i32 = 316;
 const MAGIC_NUMBER: i32 = 435885720;
 
+sol! {
+    #[derive(Debug, Serialize, Deserialize)]
+    struct PublicValuesStruct {
+        uint8 kind;
+        address player;
+        uint64 season;
+        uint256 score;
+        bytes32 nullifier;
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct ThresholdPublicValuesStruct {
+        uint8 kind;
+        address player;
+        uint64 season;
+        uint256 threshold;
+        bytes32 nullifier;
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct CommittedPublicValuesStruct {
+        uint8 kind;
+        bytes32 playerCommitment;
+        uint64 season;
+        uint256 score;
+        bytes32 nullifier;
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct CommittedThresholdPublicValuesStruct {
+        uint8 kind;
+        bytes32 playerCommitment;
+        uint64 season;
+        uint256 threshold;
+        bytes32 nullifier;
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct TournamentPublicValuesStruct {
+        uint8 kind;
+        address player;
+        uint64 tournamentId;
+        bytes32 tournamentHash;
+        uint256 score;
+        bytes32 nullifier;
+    }
+
+    /// Committed by an aggregated proof before the public values of the run
+    #[derive(Debug, Serialize, Deserialize)]
+    struct AggregateHeaderStruct {
+        uint8 kind;
+        bytes32 segmentVKey;
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct SegmentPublicValuesStruct {
+        bytes32 startState;
+        bytes32 endState;
+        bytes32 itemsHash;
+    }
+}
+
+/// First word of the public values committed for a run. Every kind of claim has its own, so the
+/// public values of one kind can't be decoded as another: the contract only accepts `Score`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PublicValuesKind {
+    /// `PublicValuesStruct`
+    Score = 1,
+    /// `ThresholdPublicValuesStruct`
+    Threshold = 2,
+    /// `CommittedPublicValuesStruct`
+    CommittedScore = 3,
+    /// `CommittedThresholdPublicValuesStruct`
+    CommittedThreshold = 4,
+    /// `AggregateHeaderStruct`, followed by the public values of the run. The contract only accepts
+    /// it if the segments were proven with the verifying key it was deployed with
+    Aggregate = 5,
+    /// `TournamentPublicValuesStruct`
+    Tournament = 6,
+}
+
+/// What the guest is asked to prove
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Task {
+    /// Replay a whole trace
+    Run(Input),
+    /// Replay part of a trace, from one committed state to another
+    Segment(SegmentInput),
+    /// Verify the proofs of all the segments of a trace and chain them together
+    Aggregate(AggregateInput),
+    /// Replay every play of a tournament session
+    Tournament(TournamentInput),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Input {
     pub encoded_trace: Vec<u8>,
     pub player: Address,
+    /// Leaderboard season the score is proven for, committed along with the score
+    pub season: u64,
+    pub claim: Claim,
+    /// If set, the guest commits `player_commitment(player, salt)` instead of the plain player address
+    pub salt: Option<B256>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentInput {
+    pub tournament: Tournament,
+    /// bincode encoded `Vec<Vec<TraceItem>>`, one trace per play, in the order they were played
+    pub encoded_session: Vec<u8>,
+    pub player: Address,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentInput {
+    /// State before the first item of the segment
+    pub state: Replay,
+    /// The encoded items of the segment, as they appear in the encoded trace
+    pub encoded_items: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentSummary {
+    pub items: u32,
+    /// `Replay::state_hash` after the last item of the segment
+    pub end_state: B256,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateInput {
+    /// The whole trace, and what to commit about it
+    pub run: Input,
+    /// `hash_u32` of the verifying key the segments were proven with
+    pub segment_vkey: [u32; 8],
+    pub segments: Vec<SegmentSummary>,
+    /// State after the last segment
+    pub final_state: Replay,
+}
+
+/// How the profiling build of the guest reads `encoded_trace`, written after a `Task::Run`. The
+/// guest that is proven always streams the trace
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DecodeStrategy {
+    /// Decode, hash and replay the trace in a single pass over the encoded bytes
+    Streaming,
+    /// Deserialize the whole `Vec<TraceItem>` with bincode and hash the buffer separately.
+    /// Only kept to compare cycle counts against `Streaming`
+    Bincode,
+}
+
+/// The `hash_u32` verifying key of the segments as committed in `AggregateHeaderStruct`, one
+/// big-endian word after the other
+pub fn segment_vkey_bytes(words: [u32; 8]) -> B256 {
+    let mut bytes = [0u8; 32];
+    for (chunk, word) in bytes.chunks_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    bytes.into()
+}
+
+/// Splits the public values of an aggregated proof into the verifying key of its segments and the
+/// public values of the run. `None` if the public values weren't committed by an aggregation
+pub fn split_aggregate(public_values: &[u8]) -> Option<(B256, &[u8])> {
+    if public_values.len() < 64 {
+        return None;
+    }
+    let (header, run) = public_values.split_at(64);
+    let header = AggregateHeaderStruct::abi_decode(header, true).ok()?;
+    (header.kind == PublicValuesKind::Aggregate as u8).then_some((header.segmentVKey, run))
+}
+
+/// keccak256 of the encoded trace, committed by the guest so the same trace can't be used twice
+pub fn nullifier(encoded_trace: &[u8]) -> B256 {
+    keccak256(encoded_trace)
+}
+
+/// keccak256(abi.encodePacked(player, salt)), so it can be checked on-chain once the salt is revealed
+pub fn player_commitment(player: Address, salt: B256) -> B256 {
+    let mut preimage = [0u8; 52];
+    preimage[..20].copy_from_slice(player.as_slice());
+    preimage[20..].copy_from_slice(salt.as_slice());
+    keccak256(preimage)
+}
+
+/// What the guest commits about the score of the replayed trace
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Claim {
+    /// Commit the exact score (`PublicValuesStruct`)
+    Score,
+    /// Commit only that the score is at least the given threshold (`ThresholdPublicValuesStruct`).
+    /// The proof can't be generated if the score is lower
+    Threshold(u32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlazkyBird {
     prover_mode: bool,
     game_is_over: bool,
@@ -27,7 +228,7 @@ pub struct FlazkyBird {
     rand: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TraceItem {
     pub action: Action,
     pub data: [u8; 4],
@@ -44,9 +245,10 @@ pub enum Action {
 
 fn new_rand(current_rand: i32) -> i32 {
     // return a value between -315 and -60
-    let mut input = current_rand * MAGIC_NUMBER;
+    // wrapping, like the release builds the game was played with, so debug builds don't panic
+    let mut input = current_rand.wrapping_mul(MAGIC_NUMBER);
     if input < 0 {
-        input = input * -1;
+        input = input.wrapping_neg();
     }
     (input % 255) -315
 }
@@ -245,12 +447,14 @@ impl FlazkyBird {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Coord {
     pub x: f32,
     pub y: i32,
 }
 
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Bird {
     position: Coord,
     speed: f32,
@@ -265,6 +469,7 @@ impl Bird {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Pipe {
     position: Coord,
 }
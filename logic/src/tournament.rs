@@ -0,0 +1,126 @@
+use std::fmt;
+
+use alloy_primitives::{keccak256, B256};
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use crate::{Replay, TraceError, TraceItem};
+
+/// A tournament where every entrant plays the same seeds, in the same order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tournament {
+    pub id: u64,
+    pub seeds: Vec<i32>,
+    /// How many times each seed can be played. The best attempt of each seed counts
+    pub max_attempts: u32,
+    /// Unix time, in seconds, from which entries are accepted
+    pub starts_at: u64,
+    /// Unix time, in seconds, after which entries aren't accepted anymore
+    pub ends_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TournamentError {
+    /// A play of the session isn't a valid trace
+    InvalidPlay { play: usize, error: TraceError },
+    /// The play doesn't use the seed being played, nor the next one of the tournament
+    UnexpectedSeed { play: usize, seed: i32 },
+    TooManyAttempts { play: usize, seed: i32 },
+    /// The session ends before every seed was played
+    MissingSeeds { played: usize },
+    /// The sum of the best scores doesn't fit in a `u32`
+    ScoreOverflow,
+    Encoding(String),
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TournamentError::InvalidPlay { play, error } => write!(f, "play {} is invalid: {}", play, error),
+            TournamentError::UnexpectedSeed { play, seed } => {
+                write!(f, "play {} uses seed {}, which isn't the next seed of the tournament", play, seed)
+            }
+            TournamentError::TooManyAttempts { play, seed } => {
+                write!(f, "play {} is one attempt too many at seed {}", play, seed)
+            }
+            TournamentError::MissingSeeds { played } => {
+                write!(f, "only {} seeds of the tournament were played", played)
+            }
+            TournamentError::ScoreOverflow => write!(f, "the sum of the best scores overflows"),
+            TournamentError::Encoding(err) => write!(f, "invalid session: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {}
+
+/// Outcome of a session replayed against a tournament
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TournamentResult {
+    /// Best score of each seed, in the order of the tournament
+    pub best_scores: Vec<u32>,
+    /// Sum of the best scores
+    pub score: u32,
+}
+
+impl Tournament {
+    /// keccak256 of the encoded tournament, committed along with its id so a session can't be
+    /// replayed against other seeds, nor entered in another window
+    pub fn hash(&self) -> B256 {
+        keccak256(bincode::serialize(self).unwrap())
+    }
+
+    /// Whether entries are accepted at `now`, in unix seconds
+    pub fn is_open(&self, now: u64) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    /// Replays the plays of a session, in order. They must go through the seeds of the tournament
+    /// in order, playing each of them at least once and at most `max_attempts` times in a row
+    pub fn replay_session(&self, plays: &[Vec<TraceItem>]) -> Result<TournamentResult, TournamentError> {
+        let mut best_scores: Vec<u32> = Vec::new();
+        let mut attempts = 0;
+        for (play, items) in plays.iter().enumerate() {
+            let mut replay =
+                Replay::new(items.len()).map_err(|error| TournamentError::InvalidPlay { play, error })?;
+            for item in items.iter() {
+                replay.step(item).map_err(|error| TournamentError::InvalidPlay { play, error })?;
+            }
+            let score = replay.finish().map_err(|error| TournamentError::InvalidPlay { play, error })?.score();
+            // `Replay` checked the play starts with `NewPlay`
+            let seed = i32::from_le_bytes(items[0].data);
+
+            let same_seed = best_scores.last().is_some() && self.seeds[best_scores.len() - 1] == seed;
+            if same_seed && attempts < self.max_attempts {
+                attempts += 1;
+                let best = best_scores.last_mut().unwrap();
+                *best = (*best).max(score);
+            } else if self.seeds.get(best_scores.len()) == Some(&seed) && self.max_attempts > 0 {
+                attempts = 1;
+                best_scores.push(score);
+            } else if same_seed {
+                return Err(TournamentError::TooManyAttempts { play, seed });
+            } else {
+                return Err(TournamentError::UnexpectedSeed { play, seed });
+            }
+        }
+        if best_scores.len() != self.seeds.len() {
+            return Err(TournamentError::MissingSeeds { played: best_scores.len() });
+        }
+        let score = best_scores
+            .iter()
+            .try_fold(0u32, |sum, score| sum.checked_add(*score))
+            .ok_or(TournamentError::ScoreOverflow)?;
+        Ok(TournamentResult { best_scores, score })
+    }
+
+    /// Same as `replay_session`, for a bincode encoded `Vec<Vec<TraceItem>>`. Bytes after the last play are
+    /// rejected, they would change the nullifier of the session
+    pub fn replay_encoded_session(&self, encoded_session: &[u8]) -> Result<TournamentResult, TournamentError> {
+        let plays: Vec<Vec<TraceItem>> = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .deserialize(encoded_session)
+            .map_err(|err| TournamentError::Encoding(err.to_string()))?;
+        self.replay_session(&plays)
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt;
+
+use alloy_primitives::{keccak256, B256};
+use serde::{Deserialize, Serialize};
+
+use crate::{Action, FlazkyBird, TraceItem};
+
+// bincode (fixint) layout of a `Vec<TraceItem>`: u64 length, then for every item
+// the u32 variant index of the action followed by the 4 data bytes
+const LEN_SIZE: usize = 8;
+const ITEM_SIZE: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceError {
+    /// The encoded trace ends before the number of items announced in its header
+    UnexpectedEnd { index: usize },
+    UnknownAction { index: usize, tag: u32 },
+    TooShort { len: usize },
+    MissingNewPlay,
+    MissingGameOver,
+    /// A `NewPlay` was found while the previous play was still running
+    NewPlayWhilePlaying { index: usize },
+    /// The bird collided more than once
+    RepeatedCollision { index: usize },
+    RepeatedGameOver { index: usize },
+    /// A `Jump` or a `GameOver` with non-zero data, which would change the nullifier but not the game
+    UnexpectedData { index: usize, data: [u8; 4] },
+    /// Bytes after the last item, which would also change the nullifier
+    TrailingBytes { len: usize },
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::UnexpectedEnd { index } => write!(f, "trace ends unexpectedly at item {}", index),
+            TraceError::UnknownAction { index, tag } => write!(f, "unknown action {} at item {}", tag, index),
+            TraceError::TooShort { len } => write!(f, "trace has {} items, at least 3 are needed", len),
+            TraceError::MissingNewPlay => write!(f, "trace doesn't start with NewPlay"),
+            TraceError::MissingGameOver => write!(f, "trace doesn't end with GameOver"),
+            TraceError::NewPlayWhilePlaying { index } => write!(f, "NewPlay at item {} while the game is running", index),
+            TraceError::RepeatedCollision { index } => write!(f, "bird collided again at item {}", index),
+            TraceError::RepeatedGameOver { index } => write!(f, "GameOver repeated at item {}", index),
+            TraceError::UnexpectedData { index, data } => {
+                write!(f, "item {} carries data {:?}, but its action takes none", index, data)
+            }
+            TraceError::TrailingBytes { len } => write!(f, "{} bytes after the last item", len),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl TraceError {
+    /// Index of the item that made the trace invalid, if there's one
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            TraceError::UnexpectedEnd { index }
+            | TraceError::UnknownAction { index, .. }
+            | TraceError::NewPlayWhilePlaying { index }
+            | TraceError::RepeatedCollision { index }
+            | TraceError::RepeatedGameOver { index }
+            | TraceError::UnexpectedData { index, .. } => Some(*index),
+            TraceError::MissingNewPlay => Some(0),
+            TraceError::TooShort { .. } | TraceError::MissingGameOver | TraceError::TrailingBytes { .. } => None,
+        }
+    }
+}
+
+/// Decodes a bincode encoded `Vec<TraceItem>` item by item, straight from the encoded bytes
+pub struct TraceDecoder<'a> {
+    bytes: &'a [u8],
+    len: usize,
+    index: usize,
+}
+
+impl<'a> TraceDecoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, TraceError> {
+        if bytes.len() < LEN_SIZE {
+            return Err(TraceError::UnexpectedEnd { index: 0 });
+        }
+        let len = u64::from_le_bytes(bytes[..LEN_SIZE].try_into().unwrap()) as usize;
+        Ok(Self { bytes, len, index: 0 })
+    }
+
+    /// Number of items announced by the encoded trace
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bytes of the length prefix
+    pub fn header(&self) -> &'a [u8] {
+        &self.bytes[..LEN_SIZE]
+    }
+
+    /// Bytes after the last item. The decoder doesn't read them, `replay_encoded` rejects them
+    pub fn trailing(&self) -> &'a [u8] {
+        let end = self.len.saturating_mul(ITEM_SIZE).saturating_add(LEN_SIZE).min(self.bytes.len());
+        &self.bytes[end..]
+    }
+
+    /// Decodes the next item, also returning the bytes it was decoded from
+    pub fn next_raw(&mut self) -> Option<Result<(TraceItem, &'a [u8]), TraceError>> {
+        if self.index == self.len {
+            return None;
+        }
+        let index = self.index;
+        let start = LEN_SIZE + index * ITEM_SIZE;
+        let Some(raw) = self.bytes.get(start..start + ITEM_SIZE) else {
+            // stop after the first error
+            self.index = self.len;
+            return Some(Err(TraceError::UnexpectedEnd { index }));
+        };
+        match decode_item(raw, index) {
+            Ok(item) => {
+                self.index += 1;
+                Some(Ok((item, raw)))
+            }
+            Err(err) => {
+                self.index = self.len;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Decodes a single encoded item. `index` is only used to report errors
+pub fn decode_item(raw: &[u8], index: usize) -> Result<TraceItem, TraceError> {
+    if raw.len() != ITEM_SIZE {
+        return Err(TraceError::UnexpectedEnd { index });
+    }
+    let tag = u32::from_le_bytes(raw[..4].try_into().unwrap());
+    let action = match tag {
+        0 => Action::NewPlay,
+        1 => Action::Jump,
+        2 => Action::ApplyPhysics,
+        3 => Action::CheckCollisionAndMovePipes,
+        4 => Action::GameOver,
+        _ => return Err(TraceError::UnknownAction { index, tag }),
+    };
+    Ok(TraceItem { action, data: raw[4..].try_into().unwrap() })
+}
+
+impl<'a> Iterator for TraceDecoder<'a> {
+    type Item = Result<TraceItem, TraceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_raw().map(|item| item.map(|(item, _)| item))
+    }
+}
+
+/// Replays a trace in prover mode, enforcing the same rules as the guest.
+/// It can be serialized, so a replay can be stopped and resumed somewhere else
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    game: FlazkyBird,
+    len: usize,
+    index: usize,
+    last_action: Option<Action>,
+    is_first_collision: bool,
+    is_first_game_over: bool,
+}
+
+impl Replay {
+    /// `len` is the total number of items of the trace that will be replayed
+    pub fn new(len: usize) -> Result<Self, TraceError> {
+        if len <= 2 {
+            return Err(TraceError::TooShort { len });
+        }
+        Ok(Self {
+            game: FlazkyBird::new(true),
+            len,
+            index: 0,
+            last_action: None,
+            is_first_collision: true,
+            is_first_game_over: true,
+        })
+    }
+
+    pub fn step(&mut self, item: &TraceItem) -> Result<(), TraceError> {
+        let index = self.index;
+        if index == 0 && item.action != Action::NewPlay {
+            return Err(TraceError::MissingNewPlay);
+        }
+        match item.action {
+            Action::NewPlay => {
+                if index != 0 {
+                    return Err(TraceError::NewPlayWhilePlaying { index });
+                }
+                self.game.new_play(i32::from_le_bytes(item.data));
+            }
+            Action::Jump => {
+                if item.data != [0; 4] {
+                    return Err(TraceError::UnexpectedData { index, data: item.data });
+                }
+                self.game.jump();
+            }
+            Action::ApplyPhysics => {
+                if self.game.apply_physics(f32::from_le_bytes(item.data)) {
+                    self.collide(index)?;
+                }
+            }
+            Action::CheckCollisionAndMovePipes => {
+                let (collision, _) = self.game.check_collision_and_move_pipes(f32::from_le_bytes(item.data));
+                if collision {
+                    self.collide(index)?;
+                }
+            }
+            Action::GameOver => {
+                if !self.is_first_game_over {
+                    return Err(TraceError::RepeatedGameOver { index });
+                }
+                if item.data != [0; 4] {
+                    return Err(TraceError::UnexpectedData { index, data: item.data });
+                }
+                self.is_first_game_over = false;
+            }
+        }
+        self.index += 1;
+        self.last_action = Some(item.action.clone());
+        Ok(())
+    }
+
+    fn collide(&mut self, index: usize) -> Result<(), TraceError> {
+        if !self.is_first_collision {
+            return Err(TraceError::RepeatedCollision { index });
+        }
+        self.is_first_collision = false;
+        Ok(())
+    }
+
+    pub fn game(&self) -> &FlazkyBird {
+        &self.game
+    }
+
+    /// Index of the next item to replay
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Encoded state, the preimage of `state_hash`
+    pub fn state_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+
+    pub fn state_hash(&self) -> B256 {
+        keccak256(self.state_bytes())
+    }
+
+    /// Checks the trace was complete and returns the final game
+    pub fn finish(self) -> Result<FlazkyBird, TraceError> {
+        if self.index < self.len {
+            return Err(TraceError::UnexpectedEnd { index: self.index });
+        }
+        if self.last_action != Some(Action::GameOver) {
+            return Err(TraceError::MissingGameOver);
+        }
+        Ok(self.game)
+    }
+}
+
+/// Decodes and replays a bincode encoded trace in a single pass. Every chunk of the encoded
+/// trace is passed to `on_bytes` in order, so the whole buffer can be hashed along the way.
+/// The trace must end with its last item, so the same game always has the same nullifier
+pub fn replay_encoded(encoded_trace: &[u8], on_bytes: impl FnMut(&[u8])) -> Result<FlazkyBird, TraceError> {
+    struct OnBytes<F>(F);
+    impl<F: FnMut(&[u8])> ReplayHook for OnBytes<F> {
+        fn bytes(&mut self, bytes: &[u8]) {
+            (self.0)(bytes)
+        }
+    }
+    replay_encoded_with(encoded_trace, &mut OnBytes(on_bytes))
+}
+
+/// Called by `replay_encoded_with` along the replay. Only `bytes` has to be implemented, the
+/// decoding and the replay of every item can also be wrapped, for instance to measure them
+pub trait ReplayHook {
+    /// Every chunk of the encoded trace, in order
+    fn bytes(&mut self, bytes: &[u8]);
+
+    /// Wraps the decoding of the header and of every item
+    fn decode<T>(&mut self, decode: impl FnOnce() -> T) -> T {
+        decode()
+    }
+
+    /// Wraps the replay of every item
+    fn step<T>(&mut self, _action: &Action, step: impl FnOnce() -> T) -> T {
+        step()
+    }
+}
+
+/// Same as `replay_encoded`, with a hook around each part of the replay
+pub fn replay_encoded_with(encoded_trace: &[u8], hook: &mut impl ReplayHook) -> Result<FlazkyBird, TraceError> {
+    let mut decoder = hook.decode(|| TraceDecoder::new(encoded_trace))?;
+    hook.bytes(decoder.header());
+    let mut replay = Replay::new(decoder.len())?;
+    while let Some(item) = hook.decode(|| decoder.next_raw()) {
+        let (item, raw) = item?;
+        hook.bytes(raw);
+        hook.step(&item.action, || replay.step(&item))?;
+    }
+    if !decoder.trailing().is_empty() {
+        return Err(TraceError::TrailingBytes { len: decoder.trailing().len() });
+    }
+    replay.finish()
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Action, TraceItem};
+
+/// Trace item with its data decoded: the seed of `NewPlay` and the delta of the physics actions.
+/// Meant for formats that are read and edited by hand, like JSON
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action")]
+pub enum TypedTraceItem {
+    NewPlay { seed: i32 },
+    Jump,
+    ApplyPhysics { delta: f32 },
+    CheckCollisionAndMovePipes { delta: f32 },
+    GameOver,
+}
+
+/// A `Jump` or `GameOver` item with data, which can't be represented by a `TypedTraceItem`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnexpectedData {
+    pub index: usize,
+    pub data: [u8; 4],
+}
+
+impl fmt::Display for UnexpectedData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "item {} has data {:?}, only NewPlay and physics items carry data", self.index, self.data)
+    }
+}
+
+impl std::error::Error for UnexpectedData {}
+
+impl TypedTraceItem {
+    /// `index` is only used to report errors
+    pub fn from_item(item: &TraceItem, index: usize) -> Result<Self, UnexpectedData> {
+        let typed = match item.action {
+            Action::NewPlay => TypedTraceItem::NewPlay { seed: i32::from_le_bytes(item.data) },
+            Action::ApplyPhysics => TypedTraceItem::ApplyPhysics { delta: f32::from_le_bytes(item.data) },
+            Action::CheckCollisionAndMovePipes => {
+                TypedTraceItem::CheckCollisionAndMovePipes { delta: f32::from_le_bytes(item.data) }
+            }
+            Action::Jump | Action::GameOver => {
+                if item.data != [0; 4] {
+                    return Err(UnexpectedData { index, data: item.data });
+                }
+                if item.action == Action::Jump {
+                    TypedTraceItem::Jump
+                } else {
+                    TypedTraceItem::GameOver
+                }
+            }
+        };
+        Ok(typed)
+    }
+
+    pub fn from_items(items: &[TraceItem]) -> Result<Vec<Self>, UnexpectedData> {
+        items.iter().enumerate().map(|(index, item)| Self::from_item(item, index)).collect()
+    }
+}
+
+impl From<TypedTraceItem> for TraceItem {
+    fn from(typed: TypedTraceItem) -> Self {
+        let (action, data) = match typed {
+            TypedTraceItem::NewPlay { seed } => (Action::NewPlay, seed.to_le_bytes()),
+            TypedTraceItem::Jump => (Action::Jump, [0; 4]),
+            TypedTraceItem::ApplyPhysics { delta } => (Action::ApplyPhysics, delta.to_le_bytes()),
+            TypedTraceItem::CheckCollisionAndMovePipes { delta } => {
+                (Action::CheckCollisionAndMovePipes, delta.to_le_bytes())
+            }
+            TypedTraceItem::GameOver => (Action::GameOver, [0; 4]),
+        };
+        TraceItem { action, data }
+    }
+}
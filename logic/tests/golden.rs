@@ -0,0 +1,193 @@
+use std::fs;
+use std::path::PathBuf;
+
+use alloy_primitives::B256;
+use flazky_bird_lib::{
+    nullifier, replay_encoded, replay_encoded_with, Action, CompactError, CompactTrace, Replay, ReplayHook, TraceDecoder,
+    TraceError, TraceItem, TypedTraceItem,
+};
+use serde::Deserialize;
+
+/// Expected values of a trace of `tests/traces`, listed in `tests/golden.json`
+#[derive(Debug, Deserialize)]
+struct Golden {
+    file: String,
+    seed: i32,
+    items: usize,
+    score: u32,
+    nullifier: B256,
+}
+
+fn traces_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/traces")
+}
+
+fn golden_traces() -> Vec<(Golden, Vec<u8>)> {
+    let golden_path = traces_dir().with_file_name("golden.json");
+    let golden: Vec<Golden> = serde_json::from_slice(&fs::read(golden_path).unwrap()).unwrap();
+    golden
+        .into_iter()
+        .map(|golden| {
+            let encoded_trace = fs::read(traces_dir().join(&golden.file)).unwrap();
+            (golden, encoded_trace)
+        })
+        .collect()
+}
+
+#[test]
+fn streaming_replay_matches_golden_score() {
+    for (golden, encoded_trace) in golden_traces() {
+        let mut hashed = Vec::new();
+        let game = replay_encoded(&encoded_trace, |bytes| hashed.extend_from_slice(bytes)).unwrap();
+        assert_eq!(game.score(), golden.score, "{}", golden.file);
+        // the nullifier covers the whole encoded trace
+        assert_eq!(hashed, encoded_trace, "{}", golden.file);
+        assert_eq!(nullifier(&encoded_trace), golden.nullifier, "{}", golden.file);
+    }
+}
+
+/// Counts what `replay_encoded_with` goes through
+#[derive(Default)]
+struct Counter {
+    bytes: usize,
+    decoded: usize,
+    steps: Vec<Action>,
+}
+
+impl ReplayHook for Counter {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.bytes += bytes.len();
+    }
+
+    fn decode<T>(&mut self, decode: impl FnOnce() -> T) -> T {
+        self.decoded += 1;
+        decode()
+    }
+
+    fn step<T>(&mut self, action: &Action, step: impl FnOnce() -> T) -> T {
+        self.steps.push(action.clone());
+        step()
+    }
+}
+
+#[test]
+fn hook_sees_every_item() {
+    for (golden, encoded_trace) in golden_traces() {
+        let mut counter = Counter::default();
+        let game = replay_encoded_with(&encoded_trace, &mut counter).unwrap();
+        assert_eq!(game.score(), golden.score, "{}", golden.file);
+        assert_eq!(counter.bytes, encoded_trace.len(), "{}", golden.file);
+        // the header, every item and the end of the trace
+        assert_eq!(counter.decoded, golden.items + 2, "{}", golden.file);
+        let items: Vec<TraceItem> = bincode::deserialize(&encoded_trace).unwrap();
+        let actions: Vec<Action> = items.into_iter().map(|item| item.action).collect();
+        assert_eq!(counter.steps, actions, "{}", golden.file);
+    }
+}
+
+#[test]
+fn decoder_matches_bincode() {
+    for (golden, encoded_trace) in golden_traces() {
+        let decoded = TraceDecoder::new(&encoded_trace).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        let deserialized: Vec<TraceItem> = bincode::deserialize(&encoded_trace).unwrap();
+        assert_eq!(decoded, deserialized, "{}", golden.file);
+        assert_eq!(decoded.len(), golden.items, "{}", golden.file);
+        assert_eq!(i32::from_le_bytes(decoded[0].data), golden.seed, "{}", golden.file);
+    }
+}
+
+#[test]
+fn resumed_replay_matches_golden_score() {
+    for (golden, encoded_trace) in golden_traces() {
+        let items: Vec<TraceItem> = bincode::deserialize(&encoded_trace).unwrap();
+        let mut replay = Replay::new(items.len()).unwrap();
+        for item in items.iter() {
+            // stop and resume the replay at every item, like segmented proving does
+            let state = replay.state_bytes();
+            replay = bincode::deserialize(&state).unwrap();
+            replay.step(item).unwrap();
+        }
+        assert_eq!(replay.finish().unwrap().score(), golden.score, "{}", golden.file);
+    }
+}
+
+#[test]
+fn compact_trace_round_trips() {
+    for (golden, encoded_trace) in golden_traces() {
+        let items: Vec<TraceItem> = bincode::deserialize(&encoded_trace).unwrap();
+        let compact = CompactTrace::from_bytes(&CompactTrace::from_items(&items).unwrap().to_bytes()).unwrap();
+        assert_eq!(compact.to_items(), items, "{}", golden.file);
+    }
+}
+
+#[test]
+fn typed_json_round_trips() {
+    for (golden, encoded_trace) in golden_traces() {
+        let items: Vec<TraceItem> = bincode::deserialize(&encoded_trace).unwrap();
+        let json = serde_json::to_string(&TypedTraceItem::from_items(&items).unwrap()).unwrap();
+        let typed: Vec<TypedTraceItem> = serde_json::from_str(&json).unwrap();
+        assert_eq!(typed[0], TypedTraceItem::NewPlay { seed: golden.seed }, "{}", golden.file);
+        let decoded: Vec<TraceItem> = typed.into_iter().map(TraceItem::from).collect();
+        assert_eq!(bincode::serialize(&decoded).unwrap(), encoded_trace, "{}", golden.file);
+    }
+}
+
+#[test]
+fn truncated_trace_is_rejected() {
+    for (golden, encoded_trace) in golden_traces() {
+        let truncated = &encoded_trace[..encoded_trace.len() - 8];
+        assert!(replay_encoded(truncated, |_| {}).is_err(), "{}", golden.file);
+    }
+}
+
+#[test]
+fn nullifier_of_a_game_cant_be_changed() {
+    for (golden, encoded_trace) in golden_traces() {
+        // a byte after the last item
+        let mut trailing = encoded_trace.clone();
+        trailing.push(0);
+        let err = replay_encoded(&trailing, |_| {}).unwrap_err();
+        assert_eq!(err, TraceError::TrailingBytes { len: 1 }, "{}", golden.file);
+
+        // data on actions that take none
+        let mut items: Vec<TraceItem> = bincode::deserialize(&encoded_trace).unwrap();
+        let last = items.len() - 1;
+        items[last].data = [1, 0, 0, 0];
+        let err = replay_encoded(&bincode::serialize(&items).unwrap(), |_| {}).unwrap_err();
+        assert_eq!(err, TraceError::UnexpectedData { index: last, data: [1, 0, 0, 0] }, "{}", golden.file);
+        items[last].data = [0; 4];
+        let jump = items.iter().position(|item| item.action == Action::Jump).unwrap();
+        items[jump].data = [0, 0, 0, 0x80];
+        let err = replay_encoded(&bincode::serialize(&items).unwrap(), |_| {}).unwrap_err();
+        assert_eq!(err.index(), Some(jump), "{}", golden.file);
+    }
+}
+
+#[test]
+fn malformed_compact_trace_is_rejected() {
+    let (_, encoded_trace) = golden_traces().remove(0);
+    let items: Vec<TraceItem> = bincode::deserialize(&encoded_trace).unwrap();
+    let compact = CompactTrace::from_items(&items).unwrap();
+
+    // a tick count the jump bitmap doesn't cover would expand to billions of items
+    let huge = CompactTrace { ticks: u32::MAX, ..compact.clone() };
+    assert!(CompactTrace::from_bytes(&huge.to_bytes()).is_err());
+
+    let mut flood = compact.clone();
+    flood.jumps[0] |= 1;
+    flood.extra_jumps.insert(0, (0, u32::MAX));
+    assert!(matches!(CompactTrace::from_bytes(&flood.to_bytes()), Err(CompactError::TooLong { .. })));
+
+    let mut unordered = compact.clone();
+    unordered.swapped = vec![2, 1];
+    assert!(CompactTrace::from_bytes(&unordered.to_bytes()).is_err());
+
+    // deltas stored on ticks of the other action would be silently dropped when expanding
+    assert!(compact.swapped.is_empty());
+    let mut misplaced = compact.clone();
+    misplaced.physics_deltas = vec![(1, 0.5)];
+    assert!(CompactTrace::from_bytes(&misplaced.to_bytes()).is_err());
+    let mut misplaced = compact;
+    misplaced.collision_deltas = vec![(0, 0.5)];
+    assert!(CompactTrace::from_bytes(&misplaced.to_bytes()).is_err());
+}
@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::PathBuf;
+
+use alloy_primitives::U256;
+use alloy_sol_types::SolType;
+use flazky_bird_lib::{
+    replay_encoded, PublicValuesKind, PublicValuesStruct, Tournament, TournamentError, TournamentPublicValuesStruct,
+    TraceItem,
+};
+
+// 2025-01-01 to 2025-01-08
+const STARTS_AT: u64 = 1735689600;
+const ENDS_AT: u64 = 1736294400;
+
+fn tournament(id: u64, seeds: Vec<i32>, max_attempts: u32) -> Tournament {
+    Tournament { id, seeds, max_attempts, starts_at: STARTS_AT, ends_at: ENDS_AT }
+}
+
+/// Plays of `tests/traces`, with their seed and score
+fn play(file: &str) -> (Vec<TraceItem>, i32, u32) {
+    let encoded_trace = fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/traces").join(file)).unwrap();
+    let items: Vec<TraceItem> = bincode::deserialize(&encoded_trace).unwrap();
+    let seed = i32::from_le_bytes(items[0].data);
+    let score = replay_encoded(&encoded_trace, |_| {}).unwrap().score();
+    (items, seed, score)
+}
+
+#[test]
+fn session_sums_best_scores() {
+    let (short, short_seed, short_score) = play("short.bin");
+    let (long, long_seed, long_score) = play("long.bin");
+    let tournament = tournament(1, vec![short_seed, long_seed], 2);
+
+    let result = tournament.replay_session(&[short.clone(), short.clone(), long.clone()]).unwrap();
+    assert_eq!(result.best_scores, vec![short_score, long_score]);
+    assert_eq!(result.score, short_score + long_score);
+
+    let mut encoded_session = bincode::serialize(&vec![short, long]).unwrap();
+    assert_eq!(tournament.replay_encoded_session(&encoded_session).unwrap().score, short_score + long_score);
+
+    // a byte after the last play would give the same session another nullifier
+    encoded_session.push(0);
+    assert!(matches!(tournament.replay_encoded_session(&encoded_session), Err(TournamentError::Encoding(_))));
+}
+
+#[test]
+fn session_follows_seed_order() {
+    let (short, short_seed, _) = play("short.bin");
+    let (long, long_seed, _) = play("long.bin");
+    let (medium, medium_seed, _) = play("medium.bin");
+    let tournament = tournament(2, vec![short_seed, long_seed], 1);
+
+    assert_eq!(
+        tournament.replay_session(&[long.clone(), short.clone()]),
+        Err(TournamentError::UnexpectedSeed { play: 0, seed: long_seed })
+    );
+    assert_eq!(
+        tournament.replay_session(&[short.clone(), medium]),
+        Err(TournamentError::UnexpectedSeed { play: 1, seed: medium_seed })
+    );
+    assert_eq!(
+        tournament.replay_session(&[short.clone(), short.clone(), long]),
+        Err(TournamentError::TooManyAttempts { play: 1, seed: short_seed })
+    );
+    assert_eq!(tournament.replay_session(&[short]), Err(TournamentError::MissingSeeds { played: 1 }));
+}
+
+#[test]
+fn tournament_hash_covers_seeds() {
+    let tournament = tournament(3, vec![1, 2, 3], 3);
+    let other_seeds = Tournament { seeds: vec![1, 2, 4], ..tournament.clone() };
+    assert_ne!(tournament.hash(), other_seeds.hash());
+    // the same seeds next week are another tournament
+    let next_week = Tournament { starts_at: ENDS_AT, ends_at: ENDS_AT + 7 * 24 * 3600, ..tournament.clone() };
+    assert_ne!(tournament.hash(), next_week.hash());
+}
+
+#[test]
+fn window_bounds_entries() {
+    let tournament = tournament(4, vec![1], 1);
+    assert!(!tournament.is_open(STARTS_AT - 1));
+    assert!(tournament.is_open(STARTS_AT));
+    assert!(tournament.is_open(ENDS_AT - 1));
+    assert!(!tournament.is_open(ENDS_AT));
+}
+
+#[test]
+fn entries_are_not_leaderboard_scores() {
+    let tournament = tournament(5, vec![1], 1);
+    let public_values = TournamentPublicValuesStruct::abi_encode(&TournamentPublicValuesStruct {
+        kind: PublicValuesKind::Tournament as u8,
+        player: Default::default(),
+        tournamentId: tournament.id,
+        tournamentHash: tournament.hash(),
+        score: U256::from(30),
+        nullifier: Default::default(),
+    });
+    // the contract decodes the leading words as a score, the kind tells them apart
+    let decoded = PublicValuesStruct::abi_decode(&public_values[..5 * 32], true).unwrap();
+    assert_ne!(decoded.kind, PublicValuesKind::Score as u8);
+}
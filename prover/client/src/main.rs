@@ -1,81 +1,225 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use flazky_bird_lib::{FlazkyBird,TraceItem,Action,Input};
+use flazky_bird_lib::{
+    Action,Input,Claim,Replay,PublicValuesStruct,ThresholdPublicValuesStruct,
+    CommittedPublicValuesStruct,CommittedThresholdPublicValuesStruct,SegmentPublicValuesStruct,
+    Task,SegmentInput,AggregateInput,TraceDecoder,decode_item,ReplayHook,replay_encoded_with,TournamentInput,TournamentPublicValuesStruct,
+    PublicValuesKind,player_commitment,AggregateHeaderStruct,segment_vkey_bytes,
+};
+#[cfg(feature = "profile")]
+use flazky_bird_lib::{DecodeStrategy, TraceItem};
 use bincode;
-use alloy_primitives::U256;
-use alloy_sol_types::sol;
-use serde::{Serialize, Deserialize};
+use alloy_primitives::{B256, U256};
 use tiny_keccak::{Keccak,Hasher};
 use alloy_sol_types::SolType;
+use sha2::{Digest, Sha256};
 
-sol! {
-    #[derive(Debug, Serialize, Deserialize)]
-    struct PublicValuesStruct {
-        address player;
-        uint256 score;
-        bytes32 nullifier;
-    }
+// Cycle tracking span, reported by the executor. Only compiled in the `profile` build of the guest
+macro_rules! span {
+    ($name:expr, $body:expr) => {{
+        #[cfg(feature = "profile")]
+        println!("cycle-tracker-report-start: {}", $name);
+        let result = $body;
+        #[cfg(feature = "profile")]
+        println!("cycle-tracker-report-end: {}", $name);
+        result
+    }};
 }
 
 pub fn main() {
     // Read the input
-    let input: Input = sp1_zkvm::io::read::<Input>();
-    let trace = bincode::deserialize::<Vec<TraceItem>>(&input.encoded_trace).unwrap();
+    match span!("read-input", sp1_zkvm::io::read::<Task>()) {
+        Task::Run(input) => run(input),
+        Task::Segment(input) => segment(input),
+        Task::Aggregate(input) => aggregate(input),
+        Task::Tournament(input) => tournament(input),
+    }
+}
 
-    // Generate nullifier
+fn run(input: Input) {
+    // only the profiling build decodes with bincode, to compare cycle counts
+    #[cfg(feature = "profile")]
+    if sp1_zkvm::io::read::<DecodeStrategy>() == DecodeStrategy::Bincode {
+        return run_bincode(input);
+    }
+
+    // Execute the game and generate the nullifier, with the decoding, hashing and replay tracked separately
+    let mut spans = Spans { hasher: Keccak::v256() };
+    let game = replay_encoded_with(&input.encoded_trace, &mut spans).unwrap();
     let mut output = [0u8; 32];
-    let mut hasher = Keccak::v256();
-    hasher.update(&input.encoded_trace);
-    hasher.finalize(&mut output);
+    span!("hash", spans.hasher.finalize(&mut output));
     let nullifier = output.into();
 
+    // Commit
+    let public_values_solidity_encoded = span!("abi-encode", encode_public_values(&input, game.score(), nullifier));
+    span!("commit", sp1_zkvm::io::commit_slice(&public_values_solidity_encoded));
+}
 
-    // assert trace is valid
-    assert!(trace.len() > 2);
-    assert!(trace[0].action == Action::NewPlay);
-    assert!(trace.last().unwrap().action == Action::GameOver);
-
-   // Execute the game
-   let mut game = FlazkyBird::new(true);
-   let mut is_first_game_over = true;
-   let mut is_first_collision = true;
-   for item in trace {
-       match item.action {
-           Action::NewPlay => {
-                let rand = i32::from_le_bytes(item.data); 
-                game.new_play(rand);
-           }
-           Action::Jump => {
-               game.jump();
-           }
-           Action::ApplyPhysics => {
-                let delta_seconds = f32::from_le_bytes(item.data);
-                if game.apply_physics(delta_seconds) {
-                    assert!(is_first_collision);
-                    is_first_collision = false;
-                }
-           }
-           Action::CheckCollisionAndMovePipes => {
-                let delta_seconds = f32::from_le_bytes(item.data);
-                let (collision, _) = game.check_collision_and_move_pipes(delta_seconds);
-                if collision {
-                    assert!(is_first_collision);
-                    is_first_collision = false;
-                }
-           }
-           Action::GameOver => {
-                assert!(is_first_game_over);
-                is_first_game_over = false;
-           }
-       }
-   }
-    
-    // Commit  
-    let public_values_solidity_encoded = PublicValuesStruct::abi_encode(&PublicValuesStruct {
-        score: U256::from(game.score()),
-        player: input.player,
-        nullifier,
+/// Deserializes the whole `Vec<TraceItem>` before replaying it, and hashes the buffer separately
+#[cfg(feature = "profile")]
+fn run_bincode(input: Input) {
+    let trace = span!("deserialize", bincode::deserialize::<Vec<TraceItem>>(&input.encoded_trace).unwrap());
+    let nullifier = span!("hash", keccak(&input.encoded_trace));
+    let mut replay = Replay::new(trace.len()).unwrap();
+    for item in trace.iter() {
+        span!(action_span(&item.action), replay.step(item).unwrap());
+    }
+    let game = replay.finish().unwrap();
+
+    let public_values_solidity_encoded = span!("abi-encode", encode_public_values(&input, game.score(), nullifier));
+    span!("commit", sp1_zkvm::io::commit_slice(&public_values_solidity_encoded));
+}
+
+/// Hashes the encoded trace for the nullifier, in cycle tracking spans
+struct Spans {
+    hasher: Keccak,
+}
+
+impl ReplayHook for Spans {
+    fn bytes(&mut self, bytes: &[u8]) {
+        span!("hash", self.hasher.update(bytes))
+    }
+
+    fn decode<T>(&mut self, decode: impl FnOnce() -> T) -> T {
+        span!("deserialize", decode())
+    }
+
+    #[cfg_attr(not(feature = "profile"), allow(unused_variables))]
+    fn step<T>(&mut self, action: &Action, step: impl FnOnce() -> T) -> T {
+        span!(action_span(action), step())
+    }
+}
+
+#[cfg_attr(not(feature = "profile"), allow(dead_code))]
+fn action_span(action: &Action) -> &'static str {
+    match action {
+        Action::NewPlay => "action:NewPlay",
+        Action::Jump => "action:Jump",
+        Action::ApplyPhysics => "action:ApplyPhysics",
+        Action::CheckCollisionAndMovePipes => "action:CheckCollisionAndMovePipes",
+        Action::GameOver => "action:GameOver",
+    }
+}
+
+fn segment(input: SegmentInput) {
+    assert!(input.encoded_items.len() % 8 == 0);
+    let start_state = keccak(&input.state.state_bytes());
+    let mut replay = input.state;
+    for raw in input.encoded_items.chunks(8) {
+        let item = decode_item(raw, replay.index()).unwrap();
+        replay.step(&item).unwrap();
+    }
+
+    sp1_zkvm::io::commit_slice(&SegmentPublicValuesStruct::abi_encode(&SegmentPublicValuesStruct {
+        startState: start_state,
+        endState: keccak(&replay.state_bytes()),
+        itemsHash: keccak(&input.encoded_items),
+    }));
+}
+
+fn aggregate(input: AggregateInput) {
+    let encoded_trace = &input.run.encoded_trace;
+    let decoder = TraceDecoder::new(encoded_trace).unwrap();
+    let nullifier = keccak(encoded_trace);
+
+    // Chain the segments, starting from a fresh replay of the trace
+    let mut state = keccak(&Replay::new(decoder.len()).unwrap().state_bytes());
+    let mut offset = decoder.header().len();
+    for segment in input.segments.iter() {
+        let end = offset + segment.items as usize * 8;
+        let segment_public_values = SegmentPublicValuesStruct::abi_encode(&SegmentPublicValuesStruct {
+            startState: state,
+            endState: segment.end_state,
+            itemsHash: keccak(&encoded_trace[offset..end]),
+        });
+        let public_values_digest = Sha256::digest(&segment_public_values);
+        sp1_zkvm::lib::verify::verify_sp1_proof(&input.segment_vkey, &public_values_digest.into());
+        state = segment.end_state;
+        offset = end;
+    }
+    // every item must be covered by a segment, and nothing may follow the last one
+    assert!(decoder.trailing().is_empty());
+    assert!(offset == encoded_trace.len());
+    assert!(keccak(&input.final_state.state_bytes()) == state);
+    let game = input.final_state.finish().unwrap();
+
+    // Commit the verifying key of the segments first, so the public values can't be mistaken for
+    // those of a single proof and the contract can check the key
+    let mut public_values_solidity_encoded = AggregateHeaderStruct::abi_encode(&AggregateHeaderStruct {
+        kind: PublicValuesKind::Aggregate as u8,
+        segmentVKey: segment_vkey_bytes(input.segment_vkey),
     });
+    public_values_solidity_encoded.extend(encode_public_values(&input.run, game.score(), nullifier));
     sp1_zkvm::io::commit_slice(&public_values_solidity_encoded);
-}
\ No newline at end of file
+}
+
+fn tournament(input: TournamentInput) {
+    // every play must follow the seeds of the tournament
+    let result = input.tournament.replay_encoded_session(&input.encoded_session).unwrap();
+
+    // Commit the tournament along with the aggregate score, so the session can't be checked against other
+    // seeds nor another window
+    sp1_zkvm::io::commit_slice(&TournamentPublicValuesStruct::abi_encode(&TournamentPublicValuesStruct {
+        kind: PublicValuesKind::Tournament as u8,
+        player: input.player,
+        tournamentId: input.tournament.id,
+        tournamentHash: input.tournament.hash(),
+        score: U256::from(result.score),
+        nullifier: keccak(&input.encoded_session),
+    }));
+}
+
+fn keccak(bytes: &[u8]) -> B256 {
+    let mut output = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    hasher.finalize(&mut output);
+    output.into()
+}
+
+fn encode_public_values(input: &Input, score: u32, nullifier: B256) -> Vec<u8> {
+    let value = match input.claim {
+        Claim::Score => U256::from(score),
+        Claim::Threshold(threshold) => {
+            // the exact score is never committed, only that it reached the threshold
+            assert!(score >= threshold);
+            U256::from(threshold)
+        }
+    };
+    match (input.salt, input.claim) {
+        (None, Claim::Score) => PublicValuesStruct::abi_encode(&PublicValuesStruct {
+            kind: PublicValuesKind::Score as u8,
+            score: value,
+            player: input.player,
+            season: input.season,
+            nullifier,
+        }),
+        (None, Claim::Threshold(_)) => ThresholdPublicValuesStruct::abi_encode(&ThresholdPublicValuesStruct {
+            kind: PublicValuesKind::Threshold as u8,
+            threshold: value,
+            player: input.player,
+            season: input.season,
+            nullifier,
+        }),
+        (Some(salt), claim) => {
+            let player_commitment = player_commitment(input.player, salt);
+            match claim {
+                Claim::Score => CommittedPublicValuesStruct::abi_encode(&CommittedPublicValuesStruct {
+                    kind: PublicValuesKind::CommittedScore as u8,
+                    score: value,
+                    playerCommitment: player_commitment,
+                    season: input.season,
+                    nullifier,
+                }),
+                Claim::Threshold(_) => CommittedThresholdPublicValuesStruct::abi_encode(&CommittedThresholdPublicValuesStruct {
+                    kind: PublicValuesKind::CommittedThreshold as u8,
+                    threshold: value,
+                    playerCommitment: player_commitment,
+                    season: input.season,
+                    nullifier,
+                }),
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::fs;
+use std::path::PathBuf;
+
+use alloy::hex;
+use alloy::network::TransactionBuilder;
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionRequest;
+use alloy::transports::Transport;
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_sol_types::SolValue;
+
+/// Where `forge build` writes the artifacts: `out` at the root of the repository (where
+/// `foundry.toml` is), or `FORGE_OUT_DIR`
+pub fn forge_out_dir() -> PathBuf {
+    std::env::var("FORGE_OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../out"))
+}
+
+/// Reads the bytecode of a contract built with `forge build`
+pub fn bytecode(contract: &str) -> eyre::Result<Bytes> {
+    let artifact_path = forge_out_dir().join(format!("{contract}.sol/{contract}.json"));
+    let artifact: serde_json::Value = serde_json::from_slice(
+        &fs::read(&artifact_path).map_err(|err| eyre::eyre!("can't read {}: {}", artifact_path.display(), err))?,
+    )?;
+    let code = artifact["bytecode"]["object"]
+        .as_str()
+        .ok_or_else(|| eyre::eyre!("{} has no bytecode", artifact_path.display()))?;
+    Ok(hex::decode(code)?.into())
+}
+
+/// Sends a contract creation transaction and returns the address of the contract
+pub async fn deploy<T, P>(provider: &P, code: Bytes) -> eyre::Result<Address>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let receipt =
+        provider.send_transaction(TransactionRequest::default().with_deploy_code(code)).await?.get_receipt().await?;
+    receipt.contract_address.ok_or_else(|| eyre::eyre!("transaction {} created no contract", receipt.transaction_hash))
+}
+
+/// Deploys `SP1VerifierMock`, which accepts any proof, and a `FlazkyBird` verifying with it. The
+/// sender owns the contract
+pub async fn deploy_with_mock_verifier<T, P>(provider: &P, vkey: B256, segment_vkey: B256) -> eyre::Result<Address>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let verifier = deploy(provider, bytecode("SP1VerifierMock")?).await?;
+    let mut code = bytecode("FlazkyBird")?.to_vec();
+    code.extend((verifier, vkey, segment_vkey).abi_encode_params());
+    deploy(provider, code.into()).await
+}
@@ -0,0 +1,121 @@
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use alloy_primitives::{Address, U256};
+use serde::Serialize;
+
+use crate::FlazkyBird;
+
+const PAGE_SIZE: u64 = 50;
+
+/// An entry of a season leaderboard
+#[derive(Debug, Clone, Serialize)]
+pub struct Standing {
+    /// Starts at 1 for the leader
+    pub rank: u64,
+    pub token_id: U256,
+    pub owner: Address,
+    pub score: u64,
+}
+
+/// Fails unless the contract accepts scores proven for `season`, the current season its owner set
+pub async fn check_season<T, P>(contract: &FlazkyBird::FlazkyBirdInstance<T, P>, season: u64) -> eyre::Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let current = contract.currentSeason().call().await?._0;
+    eyre::ensure!(
+        season == current,
+        "the contract at {} only accepts scores of season {}, not {}",
+        contract.address(),
+        current,
+        season
+    );
+    Ok(())
+}
+
+/// Finds the `_previousTokenID` to insert `score` in the leaderboard of `season` with
+/// `addLeaderboardEntry`: 0 to claim the leadership, otherwise the last entry with a score higher or
+/// equal to `score`, so the contract doesn't have to walk the list
+pub async fn previous_token_id<T, P>(
+    contract: &FlazkyBird::FlazkyBirdInstance<T, P>,
+    season: u64,
+    score: u64,
+) -> eyre::Result<U256>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let leader = contract.leader(season).call().await?._0;
+    if leader.is_zero() {
+        return Ok(U256::ZERO);
+    }
+
+    let mut previous = U256::ZERO;
+    let mut token_id = leader;
+    while !token_id.is_zero() {
+        let page = contract.getLeaderboard(season, token_id, U256::from(PAGE_SIZE)).call().await?;
+        for entry in page._0 {
+            if entry.score < score {
+                return Ok(previous);
+            }
+            previous = token_id;
+            token_id = U256::from(entry.nextTokenID);
+            if token_id.is_zero() {
+                // bottom of the leaderboard
+                break;
+            }
+        }
+    }
+    Ok(previous)
+}
+
+/// Position of `token_id` in the leaderboard of `season`, starting at 1 for the leader
+pub async fn rank<T, P>(
+    contract: &FlazkyBird::FlazkyBirdInstance<T, P>,
+    season: u64,
+    token_id: U256,
+) -> eyre::Result<Option<u64>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut rank = 1;
+    let mut current = contract.leader(season).call().await?._0;
+    while !current.is_zero() {
+        let page = contract.getLeaderboard(season, current, U256::from(PAGE_SIZE)).call().await?;
+        for entry in page._0 {
+            if current == token_id {
+                return Ok(Some(rank));
+            }
+            rank += 1;
+            current = U256::from(entry.nextTokenID);
+            if current.is_zero() {
+                break;
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The leaderboard of `season`, from the leader down, with the owner of each token
+pub async fn standings<T, P>(contract: &FlazkyBird::FlazkyBirdInstance<T, P>, season: u64) -> eyre::Result<Vec<Standing>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut standings = Vec::new();
+    let mut token_id = contract.leader(season).call().await?._0;
+    while !token_id.is_zero() {
+        let page = contract.getLeaderboard(season, token_id, U256::from(PAGE_SIZE)).call().await?;
+        for entry in page._0 {
+            let owner = contract.ownerOf(token_id).call().await?._0;
+            standings.push(Standing { rank: standings.len() as u64 + 1, token_id, owner, score: entry.score });
+            token_id = U256::from(entry.nextTokenID);
+            if token_id.is_zero() {
+                break;
+            }
+        }
+    }
+    Ok(standings)
+}
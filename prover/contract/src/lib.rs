@@ -0,0 +1,34 @@
+use alloy::sol;
+
+pub mod deploy;
+pub mod leaderboard;
+
+// The functions and events of `FlazkyBird.sol` used by the host, the indexer and the tests
+sol! {
+    #[sol(rpc)]
+    contract FlazkyBird {
+        struct LeaderboardEntry {
+            uint64 score;
+            uint64 nextTokenID;
+            uint64 season;
+        }
+
+        function vKey() external view returns (bytes32);
+        function owner() external view returns (address);
+        function currentSeason() external view returns (uint64);
+        function setCurrentSeason(uint64 season) external;
+        function leader(uint64 season) external view returns (uint256);
+        function leaderboard(uint256 tokenId) external view returns (uint64 score, uint64 nextTokenID, uint64 season);
+        function getLeaderboard(uint64 season, uint256 from, uint256 items) external view returns (LeaderboardEntry[] memory, uint256 nextIndex);
+        function nullifierMap(bytes32 nullifier) external view returns (bool);
+        function ownerOf(uint256 tokenId) external view returns (address);
+        function transferFrom(address from, address to, uint256 tokenId) external;
+        function addLeaderboardEntry(bytes calldata _publicValues, bytes calldata _proofBytes, uint256 _previousTokenID) external;
+        function unclaimed(bytes32 playerCommitment) external view returns (uint256);
+        function claim(bytes32 salt, address recipient) external;
+
+        event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+        event SeasonStarted(uint64 season);
+        event ScoreClaimed(bytes32 indexed playerCommitment, uint256 indexed tokenId, address recipient);
+    }
+}
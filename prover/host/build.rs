@@ -1,8 +1,148 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
 use sp1_build::{build_program_with_args, BuildArgs};
 
+/// Sources the guest is built from, relative to the root of the repository
+const GUEST_SOURCES: [&str; 4] = ["prover/client/Cargo.toml", "prover/client/src", "logic/Cargo.toml", "logic/src"];
+
 fn main() {
-    // build_program_with_args(
-    //     &format!("../{}", "client"),
-    //     BuildArgs { ignore_rust_version: true, elf_name: "flazky-bird".to_string(), ..Default::default() },
-    // );
+    let root = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("../..");
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let checked_in_elf = root.join("elf/flazky-bird");
+    let checked_in_manifest = root.join("elf/flazky-bird.sources");
+
+    println!("cargo:rustc-check-cfg=cfg(stale_elf)");
+    for var in ["FLAZKY_SKIP_ELF_BUILD", "FLAZKY_ELF_DOCKER", "FLAZKY_UPDATE_ELF", "FLAZKY_ALLOW_STALE_ELF", "PATH"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    for source in GUEST_SOURCES {
+        println!("cargo:rerun-if-changed={}", root.join(source).display());
+    }
+    println!("cargo:rerun-if-changed={}", root.join("elf").display());
+    // so the ELF is rebuilt once the SP1 toolchain is installed or updated
+    for cargo_prove in cargo_prove_paths() {
+        println!("cargo:rerun-if-changed={}", cargo_prove.display());
+    }
+
+    let sources = sources_hash(&root);
+    let skip = std::env::var_os("FLAZKY_SKIP_ELF_BUILD").is_some();
+    // the ELF can only be built with the SP1 toolchain
+    let has_toolchain = !skip
+        && Command::new("cargo")
+            .args(["prove", "--version"])
+            .output()
+            .is_ok_and(|output| output.status.success());
+
+    // the profiling guest is never checked in, the `profile` command needs the toolchain
+    let output_directory = out_dir.join("elf");
+    let elf = if has_toolchain {
+        // Build both ELFs from prover/client. Set FLAZKY_ELF_DOCKER to build them in the SP1 docker
+        // image, which is needed to reproduce the ELF (and its vkey) on any machine
+        let docker = std::env::var_os("FLAZKY_ELF_DOCKER").is_some();
+        for (elf_name, features) in [("flazky-bird", vec![]), ("flazky-bird-profile", vec!["profile".to_string()])] {
+            build_program_with_args(
+                "../client",
+                BuildArgs {
+                    ignore_rust_version: true,
+                    elf_name: elf_name.to_string(),
+                    output_directory: output_directory.to_string_lossy().into_owned(),
+                    features,
+                    docker,
+                    ..Default::default()
+                },
+            );
+        }
+        let elf = output_directory.join("flazky-bird");
+        // the checked-in ELF is only replaced on request, the build never writes to the source tree
+        if std::env::var_os("FLAZKY_UPDATE_ELF").is_some() {
+            fs::copy(&elf, &checked_in_elf).unwrap();
+            fs::write(&checked_in_manifest, manifest(&sources, &fs::read(&elf).unwrap())).unwrap();
+            println!("cargo:warning=elf/flazky-bird updated, update PINNED_VKEY with `check-vkey`");
+        }
+        elf
+    } else {
+        check_checked_in_elf(&checked_in_elf, &checked_in_manifest, &sources, skip);
+        checked_in_elf
+    };
+    println!("cargo:rustc-env=FLAZKY_BIRD_ELF={}", elf.display());
+    println!("cargo:rustc-env=FLAZKY_BIRD_PROFILE_ELF={}", output_directory.join("flazky-bird-profile").display());
+}
+
+/// Fails the build if the checked-in ELF wasn't built from the current guest sources, or was
+/// modified since, unless `FLAZKY_ALLOW_STALE_ELF` is set
+fn check_checked_in_elf(elf_path: &Path, manifest_path: &Path, sources: &str, skip: bool) {
+    let reason = if skip { "FLAZKY_SKIP_ELF_BUILD is set" } else { "the SP1 toolchain isn't installed" };
+    let elf = fs::read(elf_path).unwrap_or_else(|err| panic!("can't read {}: {}", elf_path.display(), err));
+    let expected = manifest(sources, &elf);
+    let recorded = fs::read_to_string(manifest_path).unwrap_or_default();
+    if recorded == expected {
+        return;
+    }
+    let problem = if recorded.lines().next() == expected.lines().next() {
+        "elf/flazky-bird doesn't match the hash recorded in elf/flazky-bird.sources"
+    } else {
+        "elf/flazky-bird wasn't built from the current guest sources (prover/client and logic)"
+    };
+    if std::env::var_os("FLAZKY_ALLOW_STALE_ELF").is_some() {
+        println!("cargo:warning={}, using it anyway as FLAZKY_ALLOW_STALE_ELF is set", problem);
+        println!("cargo:rustc-cfg=stale_elf");
+        return;
+    }
+    panic!(
+        "{} and {}. Rebuild it with script/update-elf.sh (SP1 toolchain and docker), or set FLAZKY_ALLOW_STALE_ELF=1 \
+         to use it anyway (the tests that run it are then ignored)",
+        problem, reason
+    );
+}
+
+/// Contents of `elf/flazky-bird.sources`: the hash of the guest sources the ELF was built from, and
+/// the hash of the ELF
+fn manifest(sources: &str, elf: &[u8]) -> String {
+    format!("sources {}\nelf {:x}\n", sources, Sha256::digest(elf))
+}
+
+/// sha256 of the guest sources, file by file in path order
+fn sources_hash(root: &Path) -> String {
+    let mut files = Vec::new();
+    for source in GUEST_SOURCES {
+        collect_files(&root.join(source), &mut files);
+    }
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in files {
+        let contents = fs::read(&file).unwrap();
+        let relative = file.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/");
+        hasher.update((relative.len() as u64).to_le_bytes());
+        hasher.update(relative.as_bytes());
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        for entry in fs::read_dir(path).unwrap() {
+            collect_files(&entry.unwrap().path(), files);
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+}
+
+/// Where `cargo prove` may be installed, by sp1up or `cargo install`
+fn cargo_prove_paths() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let sp1_dir = std::env::var_os("SP1_DIR").map(PathBuf::from).or_else(|| home.as_ref().map(|home| home.join(".sp1")));
+    let cargo_home =
+        std::env::var_os("CARGO_HOME").map(PathBuf::from).or_else(|| home.as_ref().map(|home| home.join(".cargo")));
+    [sp1_dir, cargo_home]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join("bin/cargo-prove"))
+        .filter(|path| path.exists())
+        .collect()
 }
@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use eyre::eyre;
+use alloy_primitives::Address;
+use flazky_bird_lib::{nullifier, Claim, Input, Task};
+use serde::Serialize;
+use sp1_sdk::{ProverClient, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+
+use crate::fixture::{ProofFixture, ProofType};
+use crate::formats::{self, TraceFormat};
+use crate::preflight;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchRecord {
+    pub trace: String,
+    pub success: bool,
+    pub score: Option<u32>,
+    pub nullifier: Option<String>,
+    pub cycles: Option<u64>,
+    pub fixture: Option<String>,
+    pub error: Option<String>,
+}
+
+pub struct BatchOptions {
+    /// `None` to only execute the traces
+    pub proof_type: Option<ProofType>,
+    pub season: u64,
+    pub concurrency: usize,
+    pub output_dir: PathBuf,
+}
+
+/// Traces of a directory (files with one of `TraceFormat::EXTENSIONS`), or matching a glob pattern
+pub fn find_traces(pattern: &str) -> eyre::Result<Vec<PathBuf>> {
+    let mut traces = if Path::new(pattern).is_dir() {
+        fs::read_dir(pattern)?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<eyre::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|path| path.is_file() && is_trace(path))
+            .collect::<Vec<_>>()
+    } else {
+        glob::glob(pattern)?.collect::<Result<Vec<_>, _>>()?
+    };
+    traces.sort();
+    Ok(traces)
+}
+
+fn is_trace(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| TraceFormat::EXTENSIONS.contains(&extension))
+}
+
+/// Executes, and optionally proves, the score of every trace for `player`, `concurrency` traces at a time.
+/// Fixtures are named `<player>-<score>-<nullifier>.json`
+pub fn run_batch(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    vk: &SP1VerifyingKey,
+    traces: &[PathBuf],
+    player: Address,
+    options: &BatchOptions,
+) -> Vec<BatchRecord> {
+    let next = AtomicUsize::new(0);
+    let records = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..options.concurrency.max(1) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(trace) = traces.get(i) else { break };
+                let record = run_trace(client, pk, vk, trace, player, options);
+                match &record.error {
+                    None => println!("{}: score {}", record.trace, record.score.unwrap_or_default()),
+                    Some(err) => println!("{}: failed, {}", record.trace, err),
+                }
+                records.lock().unwrap().push((i, record));
+            });
+        }
+    });
+    let mut records = records.into_inner().unwrap();
+    records.sort_by_key(|(i, _)| *i);
+    records.into_iter().map(|(_, record)| record).collect()
+}
+
+fn run_trace(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    vk: &SP1VerifyingKey,
+    trace: &Path,
+    player: Address,
+    options: &BatchOptions,
+) -> BatchRecord {
+    let mut record = BatchRecord {
+        trace: trace.display().to_string(),
+        success: false,
+        score: None,
+        nullifier: None,
+        cycles: None,
+        fixture: None,
+        error: None,
+    };
+    if let Err(err) = prove_trace(client, pk, vk, trace, player, options, &mut record) {
+        record.error = Some(err.to_string());
+    } else {
+        record.success = true;
+    }
+    record
+}
+
+fn prove_trace(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    vk: &SP1VerifyingKey,
+    trace: &Path,
+    player: Address,
+    options: &BatchOptions,
+    record: &mut BatchRecord,
+) -> eyre::Result<()> {
+    let input = Input {
+        encoded_trace: formats::read_encoded(trace)?,
+        player,
+        season: options.season,
+        claim: Claim::Score,
+        salt: None,
+    };
+    let nullifier = nullifier(&input.encoded_trace);
+    record.nullifier = Some(nullifier.to_string());
+    let score = preflight::preflight(&input)?;
+    record.score = Some(score);
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&Task::Run(input.clone()));
+    let (public_values, execution_report) =
+        client.execute(&pk.elf, stdin.clone()).run().map_err(|err| eyre!("execution failed: {}", err))?;
+    record.cycles = Some(execution_report.total_instruction_count());
+    preflight::check_committed_score(&input, score, public_values.as_slice())?;
+
+    if let Some(proof_type) = options.proof_type {
+        let proof = proof_type.prove(client, pk, stdin)?;
+        preflight::check_committed_score(&input, score, proof.public_values.as_slice())?;
+        let fixture = ProofFixture::new(&proof, proof_type, vk, &input);
+        let fixture_path = options.output_dir.join(format!("{}-{}-{}.json", input.player, score, nullifier));
+        fixture.save(&fixture_path, &proof, &input)?;
+        record.fixture = Some(fixture_path.display().to_string());
+    }
+    Ok(())
+}
+
+/// Writes the records as CSV if `path` ends with `.csv`, as JSON otherwise
+pub fn write_summary(path: &Path, records: &[BatchRecord]) -> eyre::Result<()> {
+    if path.extension().is_some_and(|extension| extension == "csv") {
+        let mut writer = csv::Writer::from_path(path)?;
+        for record in records {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+    } else {
+        fs::write(path, serde_json::to_string_pretty(records)?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use alloy::hex;
+use alloy_sol_types::SolType;
+use flazky_bird_lib::{
+    Claim, CommittedPublicValuesStruct, CommittedThresholdPublicValuesStruct, Input, PublicValuesStruct,
+    ThresholdPublicValuesStruct, player_commitment, split_aggregate,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofType {
+    /// Fastest to generate, but large and only verifiable off-chain
+    Core,
+    /// Constant size STARK, only verifiable off-chain
+    Compressed,
+    #[default]
+    Plonk,
+    Groth16,
+}
+
+impl ProofType {
+    /// Whether the proof can be verified by the SP1 verifier contracts
+    pub fn is_onchain(self) -> bool {
+        matches!(self, ProofType::Plonk | ProofType::Groth16)
+    }
+
+    pub fn prove(self, client: &ProverClient, pk: &SP1ProvingKey, stdin: SP1Stdin) -> eyre::Result<SP1ProofWithPublicValues> {
+        let builder = client.prove(pk, stdin);
+        let proof = match self {
+            ProofType::Core => builder.core().run(),
+            ProofType::Compressed => builder.compressed().run(),
+            ProofType::Plonk => builder.plonk().run(),
+            ProofType::Groth16 => builder.groth16().run(),
+        };
+        proof.map_err(|err| eyre::eyre!("proving failed: {}", err))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofFixture {
+    /// Fixtures generated before the proof type was selectable are plonk proofs
+    #[serde(default)]
+    pub proof_type: ProofType,
+    pub player: String,
+    pub season: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<String>,
+    pub nullifier: String,
+    pub public_values: String,
+    /// Empty for proofs that can't be verified on-chain, the full proof is always stored in `proof_path`
+    pub proof: String,
+    pub vkey: String,
+    /// Verifying key the segments of an aggregated proof were proven with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_vkey: Option<String>,
+    /// `_previousTokenID` to use in `addLeaderboardEntry`, when the leaderboard was read while proving
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_token_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSalt {
+    pub player: String,
+    pub salt: String,
+    pub player_commitment: String,
+}
+
+impl ProofFixture {
+    /// Aggregated proofs commit the verifying key of the segments before the usual public values
+    pub fn new(proof: &SP1ProofWithPublicValues, proof_type: ProofType, vk: &SP1VerifyingKey, input: &Input) -> Self {
+        let public_values_solidity_encoded = proof.public_values.as_slice();
+        let (committed_values, segment_vkey) = match split_aggregate(public_values_solidity_encoded) {
+            Some((segment_vkey, committed_values)) => (committed_values, Some(segment_vkey.to_string())),
+            None => (public_values_solidity_encoded, None),
+        };
+        let (player, season, score, threshold, nullifier) = match (input.salt, input.claim) {
+            (None, Claim::Score) => {
+                let decoded_values = PublicValuesStruct::abi_decode(committed_values, true).unwrap();
+                (
+                    decoded_values.player.to_string(),
+                    decoded_values.season,
+                    Some(decoded_values.score.to_string()),
+                    None,
+                    decoded_values.nullifier,
+                )
+            }
+            (None, Claim::Threshold(_)) => {
+                let decoded_values = ThresholdPublicValuesStruct::abi_decode(committed_values, true).unwrap();
+                (
+                    decoded_values.player.to_string(),
+                    decoded_values.season,
+                    None,
+                    Some(decoded_values.threshold.to_string()),
+                    decoded_values.nullifier,
+                )
+            }
+            (Some(_), Claim::Score) => {
+                let decoded_values = CommittedPublicValuesStruct::abi_decode(committed_values, true).unwrap();
+                (
+                    decoded_values.playerCommitment.to_string(),
+                    decoded_values.season,
+                    Some(decoded_values.score.to_string()),
+                    None,
+                    decoded_values.nullifier,
+                )
+            }
+            (Some(_), Claim::Threshold(_)) => {
+                let decoded_values = CommittedThresholdPublicValuesStruct::abi_decode(committed_values, true).unwrap();
+                (
+                    decoded_values.playerCommitment.to_string(),
+                    decoded_values.season,
+                    None,
+                    Some(decoded_values.threshold.to_string()),
+                    decoded_values.nullifier,
+                )
+            }
+        };
+        let proof_bytes = if proof_type.is_onchain() { proof.bytes() } else { Vec::new() };
+        ProofFixture {
+            proof_type,
+            player,
+            season,
+            score,
+            threshold,
+            nullifier: nullifier.to_string(),
+            vkey: vk.bytes32().to_string(),
+            public_values: format!("0x{}", hex::encode(public_values_solidity_encoded)),
+            proof: format!("0x{}", hex::encode(proof_bytes)),
+            segment_vkey,
+            previous_token_id: None,
+        }
+    }
+
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let fixture: serde_json::Value = serde_json::from_slice(&fs::read(path)?)?;
+        // their public values have no season, the contract can't accept them
+        eyre::ensure!(
+            fixture.get("season").is_some(),
+            "{} was generated before seasons were committed, prove the trace again",
+            path.display()
+        );
+        Ok(serde_json::from_value(fixture)?)
+    }
+
+    /// Writes the fixture, the full SP1 proof next to it (see `proof_path`) and, for committed
+    /// players, the salt (see `salt_path`)
+    pub fn save(&self, path: &Path, proof: &SP1ProofWithPublicValues, input: &Input) -> eyre::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        let proof_path = proof_path(path);
+        proof
+            .save(&proof_path)
+            .map_err(|err| eyre::eyre!("failed to save the proof to {}: {}", proof_path.display(), err))?;
+
+        if let Some(salt) = input.salt {
+            // Without the salt the player can't prove that the commitment belongs to them
+            let salt_file = PlayerSalt {
+                player: input.player.to_string(),
+                salt: salt.to_string(),
+                player_commitment: player_commitment(input.player, salt).to_string(),
+            };
+            fs::write(salt_path(path), serde_json::to_string_pretty(&salt_file)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// The full SP1 proof is stored next to the fixture, `flazky.json` -> `flazky.proof`
+pub fn proof_path(fixture_path: &Path) -> PathBuf {
+    fixture_path.with_extension("proof")
+}
+
+/// The player salt is stored next to the fixture, `flazky.json` -> `flazky-salt.json`
+pub fn salt_path(fixture_path: &Path) -> PathBuf {
+    let stem = fixture_path.file_stem().unwrap_or_default().to_string_lossy();
+    fixture_path.with_file_name(format!("{}-salt.json", stem))
+}
@@ -0,0 +1,104 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use flazky_bird_lib::{CompactTrace, TraceDecoder, TraceItem, TypedTraceItem};
+
+/// Formats a trace can be stored in
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TraceFormat {
+    /// bincode encoded `Vec<TraceItem>`, what the frontend exports and the guest reads
+    Bincode,
+    /// JSON array of typed items
+    Json,
+    /// One typed item per line
+    Ndjson,
+    /// bincode encoded `CompactTrace`
+    Compact,
+}
+
+impl TraceFormat {
+    /// Extensions of the files `find_traces` picks up in a directory, bincode traces end with `.bin`
+    pub const EXTENSIONS: [&'static str; 5] = ["bin", "json", "ndjson", "jsonl", "compact"];
+
+    /// Guesses the format from the extension: `.json`, `.ndjson` or `.jsonl`, `.compact`, and
+    /// bincode for anything else
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => TraceFormat::Json,
+            Some("ndjson") | Some("jsonl") => TraceFormat::Ndjson,
+            Some("compact") => TraceFormat::Compact,
+            _ => TraceFormat::Bincode,
+        }
+    }
+}
+
+/// Decodes a trace. Bytes after the last item of a bincode trace are rejected: they are part of the
+/// nullifier but no other format can hold them
+pub fn decode(bytes: &[u8], format: TraceFormat) -> eyre::Result<Vec<TraceItem>> {
+    let items = match format {
+        TraceFormat::Bincode => {
+            let decoder = TraceDecoder::new(bytes)?;
+            let trailing = decoder.trailing().len();
+            eyre::ensure!(
+                trailing == 0,
+                "{} bytes after the last item, they would be lost and the nullifier would change",
+                trailing
+            );
+            decoder.collect::<Result<Vec<TraceItem>, _>>()?
+        }
+        TraceFormat::Json => {
+            let typed: Vec<TypedTraceItem> = serde_json::from_slice(bytes)?;
+            typed.into_iter().map(TraceItem::from).collect()
+        }
+        TraceFormat::Ndjson => {
+            let mut items = Vec::new();
+            for (line_number, line) in std::str::from_utf8(bytes)?.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let typed: TypedTraceItem = serde_json::from_str(line)
+                    .map_err(|err| eyre::eyre!("line {}: {}", line_number + 1, err))?;
+                items.push(typed.into());
+            }
+            items
+        }
+        TraceFormat::Compact => CompactTrace::from_bytes(bytes)?.to_items(),
+    };
+    Ok(items)
+}
+
+/// Encodes the items, failing if they can't be decoded back to exactly the same items
+pub fn encode(items: &[TraceItem], format: TraceFormat) -> eyre::Result<Vec<u8>> {
+    let bytes = match format {
+        TraceFormat::Bincode => bincode::serialize(items)?,
+        TraceFormat::Json | TraceFormat::Ndjson => {
+            let typed = TypedTraceItem::from_items(items)?;
+            if format == TraceFormat::Json {
+                serde_json::to_vec_pretty(&typed)?
+            } else {
+                let mut bytes = Vec::new();
+                for item in typed.iter() {
+                    serde_json::to_writer(&mut bytes, item)?;
+                    bytes.push(b'\n');
+                }
+                bytes
+            }
+        }
+        TraceFormat::Compact => CompactTrace::from_items(items)?.to_bytes(),
+    };
+    // JSON has no NaN or infinite numbers, for instance
+    let decoded = decode(&bytes, format)
+        .map_err(|err| eyre::eyre!("the trace can't be stored as {:?}: {}", format, err))?;
+    eyre::ensure!(decoded == items, "the trace can't be stored as {:?} without changing it", format);
+    Ok(bytes)
+}
+
+/// Reads a trace in any format, guessed from its extension, and returns it bincode encoded as the
+/// guest expects it. Bincode files are returned as is
+pub fn read_encoded(path: &Path) -> eyre::Result<Vec<u8>> {
+    let bytes = std::fs::read(path)?;
+    match TraceFormat::from_path(path) {
+        TraceFormat::Bincode => Ok(bytes),
+        format => Ok(bincode::serialize(&decode(&bytes, format)?)?),
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::Address;
+use flazky_bird_lib::{Claim, Input, Task};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, ProverClient, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+
+use crate::fixture::{ProofFixture, ProofType};
+use crate::formats;
+use crate::preflight;
+
+/// Fixtures loaded by `contracts/FlazkyBird.t.sol`, with the keys the test reads with `vm.parseJson*`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractFixtures {
+    pub vkey: String,
+    /// Season all the entries are proven for
+    pub season: u64,
+    pub count: usize,
+    /// Entries in the order they have to be added to a fresh leaderboard
+    pub entries: Vec<ContractFixture>,
+    /// The trace of the first entry, proven for another player: same nullifier, so it must be rejected
+    pub duplicate: ContractFixture,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractFixture {
+    pub trace: String,
+    pub player: String,
+    pub score: u64,
+    pub nullifier: String,
+    pub public_values: String,
+    pub proof: String,
+    /// `_previousTokenID` once all the previous entries were added
+    pub previous_token_id: u64,
+}
+
+pub fn default_output_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../contracts/fixtures/flazky-fixtures.json")
+}
+
+/// Proves every trace, for `players` in turn, and builds the fixtures of the contract tests
+pub fn generate(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    vk: &SP1VerifyingKey,
+    traces: &[PathBuf],
+    players: &[Address],
+    season: u64,
+    proof_type: ProofType,
+) -> eyre::Result<ContractFixtures> {
+    eyre::ensure!(!traces.is_empty(), "at least one trace is needed");
+    eyre::ensure!(players.len() >= 2, "at least two players are needed for the duplicate nullifier case");
+
+    // (token ID, score) in leaderboard order
+    let mut leaderboard: Vec<(u64, u64)> = Vec::new();
+    let mut entries = Vec::new();
+    for (index, trace) in traces.iter().enumerate() {
+        let mut entry = prove_entry(client, pk, vk, trace, players[index % players.len()], season, proof_type)?;
+        // the entry goes after the last one with a higher or equal score, like `previous_token_id`
+        let position = leaderboard.iter().take_while(|(_, score)| *score >= entry.score).count();
+        entry.previous_token_id = position.checked_sub(1).map_or(0, |previous| leaderboard[previous].0);
+        leaderboard.insert(position, (index as u64 + 1, entry.score));
+        println!("{}: score {} for {} after token ID {}", entry.trace, entry.score, entry.player, entry.previous_token_id);
+        entries.push(entry);
+    }
+    let duplicate = prove_entry(client, pk, vk, &traces[0], players[1], season, proof_type)?;
+
+    Ok(ContractFixtures { vkey: vk.bytes32(), season, count: entries.len(), entries, duplicate })
+}
+
+fn prove_entry(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    vk: &SP1VerifyingKey,
+    trace: &Path,
+    player: Address,
+    season: u64,
+    proof_type: ProofType,
+) -> eyre::Result<ContractFixture> {
+    let input = Input {
+        encoded_trace: formats::read_encoded(trace)?,
+        player,
+        season,
+        claim: Claim::Score,
+        salt: None,
+    };
+    let score = preflight::preflight(&input)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&Task::Run(input.clone()));
+    let proof = proof_type.prove(client, pk, stdin)?;
+    preflight::check_committed_score(&input, score, proof.public_values.as_slice())?;
+
+    let fixture = ProofFixture::new(&proof, proof_type, vk, &input);
+    Ok(ContractFixture {
+        trace: trace.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        player: fixture.player,
+        score: score.into(),
+        nullifier: fixture.nullifier,
+        public_values: fixture.public_values,
+        proof: fixture.proof,
+        previous_token_id: 0,
+    })
+}
+
+impl ContractFixtures {
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
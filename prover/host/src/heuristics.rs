@@ -0,0 +1,144 @@
+use flazky_bird_lib::{Action, Replay, TraceDecoder, TraceItem, BIRD_WIDTH, PIPE_WIDTH};
+use serde::Serialize;
+
+// Below these, the signal looks scripted, above them it looks fully human
+const JUMP_INTERVAL_CV: (f32, f32) = (0.05, 0.3);
+const REACTION_SECONDS: (f32, f32) = (0.05, 0.2);
+const DELTA_STD_DEV: (f32, f32) = (0.0002, 0.002);
+
+// Minimum samples for a signal to be taken into account
+const MIN_JUMP_INTERVALS: usize = 5;
+const MIN_REACTIONS: usize = 3;
+const MIN_DELTAS: usize = 30;
+
+// A jump later than this after a gap change isn't a reaction to it
+const MAX_REACTION_SECONDS: f32 = 1.;
+
+/// How human-like a trace looks. Each signal is only computed when the trace has enough samples
+#[derive(Debug, Clone, Serialize)]
+pub struct HumanityReport {
+    /// Coefficient of variation of the time between jumps. A bot tends to jump at a steady pace
+    pub jump_interval_cv: Option<f32>,
+    /// Median time between the next gap changing and the following jump. Humans need ~200ms to react
+    pub median_reaction_seconds: Option<f32>,
+    /// Standard deviation of the physics deltas. The frontend adds the time left over by its timer,
+    /// so a browser never produces perfectly regular frames
+    pub delta_std_dev: Option<f32>,
+    /// From 0 (scripted) to 1 (human), the mean of the signals that could be computed. `None` when
+    /// the trace is too short for any of them: it's unknown, not human
+    pub score: Option<f32>,
+    pub reasons: Vec<String>,
+}
+
+/// Scores how human-like a trace looks, from its jump cadence, its reactions to gap changes and
+/// the jitter of its frames
+pub fn analyze(encoded_trace: &[u8]) -> eyre::Result<HumanityReport> {
+    let items = TraceDecoder::new(encoded_trace)?.collect::<Result<Vec<TraceItem>, _>>()?;
+    let mut replay = Replay::new(items.len())?;
+
+    let mut elapsed = 0.;
+    let mut deltas = Vec::new();
+    let mut jumps = Vec::new();
+    // times at which the next gap to fly through changed
+    let mut gap_changes = Vec::new();
+    let mut next_gap = None;
+    for item in items.iter() {
+        replay.step(item)?;
+        match item.action {
+            Action::Jump => jumps.push(elapsed),
+            Action::ApplyPhysics => {
+                let delta = f32::from_le_bytes(item.data);
+                elapsed += delta;
+                deltas.push(delta);
+            }
+            Action::CheckCollisionAndMovePipes => {
+                let game = replay.game();
+                let bird = game.bird_position();
+                // nearest lower pipe the bird hasn't passed yet (even pipes are the lower ones)
+                let gap = game
+                    .get_pipe_positions()
+                    .into_iter()
+                    .step_by(2)
+                    .filter(|pipe| pipe.x + PIPE_WIDTH / 2. >= bird.x - BIRD_WIDTH / 2.)
+                    .min_by(|a, b| a.x.total_cmp(&b.x))
+                    .map(|pipe| pipe.y);
+                if next_gap.is_some() && gap != next_gap {
+                    gap_changes.push(elapsed);
+                }
+                next_gap = gap;
+            }
+            Action::NewPlay | Action::GameOver => {}
+        }
+    }
+
+    let mut reasons = Vec::new();
+    let mut signals = Vec::new();
+
+    let intervals: Vec<f32> = jumps.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let jump_interval_cv = (intervals.len() >= MIN_JUMP_INTERVALS).then(|| {
+        let (mean, std_dev) = mean_std_dev(&intervals);
+        if mean > 0. { std_dev / mean } else { 0. }
+    });
+    if let Some(cv) = jump_interval_cv {
+        signals.push(signal(cv, JUMP_INTERVAL_CV));
+        if cv < JUMP_INTERVAL_CV.0 {
+            reasons.push(format!("jumps are evenly spaced (coefficient of variation {:.3})", cv));
+        }
+    }
+
+    let mut reactions: Vec<f32> = gap_changes
+        .iter()
+        .filter_map(|change| jumps.iter().find(|jump| *jump >= change).map(|jump| jump - change))
+        .filter(|reaction| *reaction <= MAX_REACTION_SECONDS)
+        .collect();
+    reactions.sort_by(f32::total_cmp);
+    let median_reaction_seconds = (reactions.len() >= MIN_REACTIONS).then(|| reactions[reactions.len() / 2]);
+    if let Some(reaction) = median_reaction_seconds {
+        signals.push(signal(reaction, REACTION_SECONDS));
+        if reaction < REACTION_SECONDS.0 {
+            reasons.push(format!("reacts to gap changes in {:.0}ms", reaction * 1000.));
+        }
+    }
+
+    let delta_std_dev = (deltas.len() >= MIN_DELTAS).then(|| mean_std_dev(&deltas).1);
+    if let Some(std_dev) = delta_std_dev {
+        signals.push(signal(std_dev, DELTA_STD_DEV));
+        if std_dev < DELTA_STD_DEV.0 {
+            reasons.push(format!("frame deltas have no jitter (std dev {:.5}s)", std_dev));
+        }
+    }
+
+    let score = (!signals.is_empty()).then(|| signals.iter().sum::<f32>() / signals.len() as f32);
+    if score.is_none() {
+        reasons.push("the trace is too short to tell".to_string());
+    }
+    Ok(HumanityReport { jump_interval_cv, median_reaction_seconds, delta_std_dev, score, reasons })
+}
+
+/// Rejects traces that look less human than `min_score`, and the ones too short to be scored
+/// unless `allow_unknown` is set
+pub fn check(encoded_trace: &[u8], min_score: f32, allow_unknown: bool) -> eyre::Result<HumanityReport> {
+    let report = analyze(encoded_trace)?;
+    match report.score {
+        Some(score) => eyre::ensure!(
+            score >= min_score,
+            "trace looks scripted: humanity score {:.2} is lower than {:.2} ({})",
+            score,
+            min_score,
+            report.reasons.join(", ")
+        ),
+        None => eyre::ensure!(allow_unknown, "the humanity of the trace is unknown, it's too short to be scored"),
+    }
+    Ok(report)
+}
+
+/// 0 below `low`, 1 above `high`, linear in between
+fn signal(value: f32, (low, high): (f32, f32)) -> f32 {
+    ((value - low) / (high - low)).clamp(0., 1.)
+}
+
+fn mean_std_dev(values: &[f32]) -> (f32, f32) {
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / values.len() as f32;
+    (mean, variance.sqrt())
+}
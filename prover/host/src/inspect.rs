@@ -0,0 +1,339 @@
+use std::collections::BTreeMap;
+
+use flazky_bird_lib::{
+    Action, Coord, Replay, TraceDecoder, TraceError, TraceItem, BIRD_WIDTH, PIPE_HEIGHT, PIPE_WIDTH, WINDOW_Y,
+};
+
+use crate::heuristics;
+
+// The frontend steps the game every 0.03s and adds the time left over by its timer, so a delta
+// is never lower than 0.03s, and a much larger one means the game was lagging or tampered with
+const MIN_DELTA: f32 = 0.03;
+const MAX_DELTA: f32 = 0.1;
+
+const TIMELINE_COLUMNS: usize = 100;
+const TIMELINE_ROWS: usize = 20;
+// lowest position of the bird
+const GROUND_Y: i32 = -174;
+
+/// Decodes a trace and prints a summary of it, statistics, a timeline of the play and anomalies
+pub fn inspect(encoded_trace: &[u8]) -> eyre::Result<()> {
+    let items = TraceDecoder::new(encoded_trace)?.collect::<Result<Vec<TraceItem>, _>>()?;
+    println!("Items: {}", items.len());
+    if let Some(TraceItem { action: Action::NewPlay, data }) = items.first() {
+        println!("Seed: {}", i32::from_le_bytes(*data));
+    }
+
+    let actions = [
+        Action::NewPlay,
+        Action::Jump,
+        Action::ApplyPhysics,
+        Action::CheckCollisionAndMovePipes,
+        Action::GameOver,
+    ];
+    for action in actions {
+        let count = items.iter().filter(|item| item.action == action).count();
+        println!("{:?}: {}", action, count);
+    }
+    let duration: f32 = items
+        .iter()
+        .filter(|item| item.action == Action::ApplyPhysics)
+        .map(|item| f32::from_le_bytes(item.data))
+        .sum();
+    println!("Duration: {:.2}s", duration);
+
+    let (frames, result) = replay_frames(&items);
+    match &result {
+        Ok(score) => println!("Score: {}", score),
+        Err(err) => println!("Invalid trace: {}", err),
+    }
+
+    print_deltas(&items);
+    print_jumps(&frames);
+    print_points(&frames);
+    print_timeline(&frames);
+    print_anomalies(&items, result.err());
+    print_humanity(encoded_trace);
+    Ok(())
+}
+
+/// State of the game after an `ApplyPhysics` item
+struct Frame {
+    index: usize,
+    elapsed: f32,
+    bird: Coord,
+    pipes: Vec<Coord>,
+    jumps: usize,
+    score: u32,
+    collision: bool,
+}
+
+/// Replays the trace, recording every frame until the end of the trace or the first error
+fn replay_frames(items: &[TraceItem]) -> (Vec<Frame>, Result<u32, TraceError>) {
+    let mut replay = match Replay::new(items.len()) {
+        Ok(replay) => replay,
+        Err(err) => return (Vec::new(), Err(err)),
+    };
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut elapsed = 0.;
+    let mut jumps = 0;
+    for (index, item) in items.iter().enumerate() {
+        if let Err(err) = replay.step(item) {
+            return (frames, Err(err));
+        }
+        let game = replay.game();
+        match item.action {
+            Action::Jump => jumps += 1,
+            Action::ApplyPhysics => {
+                elapsed += f32::from_le_bytes(item.data);
+                frames.push(Frame {
+                    index,
+                    elapsed,
+                    bird: game.bird_position(),
+                    pipes: game.get_pipe_positions(),
+                    jumps,
+                    score: game.score(),
+                    collision: false,
+                });
+                jumps = 0;
+            }
+            Action::CheckCollisionAndMovePipes => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.pipes = game.get_pipe_positions();
+                    frame.score = game.score();
+                }
+            }
+            Action::GameOver => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.collision = true;
+                }
+            }
+            Action::NewPlay => {}
+        }
+    }
+    let score = replay.finish().map(|game| game.score());
+    (frames, score)
+}
+
+/// min, max, mean and standard deviation
+fn stats(values: &[f32]) -> Option<(f32, f32, f32, f32)> {
+    if values.is_empty() {
+        return None;
+    }
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / values.len() as f32;
+    Some((min, max, mean, variance.sqrt()))
+}
+
+fn print_deltas(items: &[TraceItem]) {
+    for action in [Action::ApplyPhysics, Action::CheckCollisionAndMovePipes] {
+        let deltas: Vec<f32> = items
+            .iter()
+            .filter(|item| item.action == action)
+            .map(|item| f32::from_le_bytes(item.data))
+            .collect();
+        let Some((min, max, mean, std_dev)) = stats(&deltas) else {
+            continue;
+        };
+        println!();
+        println!(
+            "{:?} deltas: min {:.4}s, max {:.4}s, mean {:.4}s, std dev {:.4}s",
+            action, min, max, mean, std_dev
+        );
+        // distribution, by millisecond
+        let mut buckets = BTreeMap::new();
+        for delta in deltas.iter() {
+            *buckets.entry((delta * 1000.).round() as i64).or_insert(0usize) += 1;
+        }
+        let largest = buckets.values().copied().max().unwrap_or(1);
+        for (millis, count) in buckets {
+            println!("  {:>4}ms {:>6} {}", millis, count, "#".repeat((count * 40).div_ceil(largest)));
+        }
+    }
+}
+
+fn print_jumps(frames: &[Frame]) {
+    // ticks and times of the frames preceded by a jump
+    let jumps: Vec<(usize, f32)> = frames
+        .iter()
+        .enumerate()
+        .filter(|(_, frame)| frame.jumps > 0)
+        .map(|(tick, frame)| (tick, frame.elapsed))
+        .collect();
+    let intervals: Vec<f32> = jumps.windows(2).map(|pair| pair[1].1 - pair[0].1).collect();
+    let tick_intervals: Vec<f32> = jumps.windows(2).map(|pair| (pair[1].0 - pair[0].0) as f32).collect();
+    println!();
+    println!("Jumps: {}", frames.iter().map(|frame| frame.jumps).sum::<usize>());
+    if let Some(elapsed) = frames.last().map(|frame| frame.elapsed).filter(|elapsed| *elapsed > 0.) {
+        println!("  {:.2} jumps per second", jumps.len() as f32 / elapsed);
+    }
+    if let (Some((min, max, mean, std_dev)), Some((_, _, mean_ticks, _))) = (stats(&intervals), stats(&tick_intervals)) {
+        println!(
+            "  interval: min {:.3}s, max {:.3}s, mean {:.3}s (~{:.1} ticks), std dev {:.3}s",
+            min, max, mean, mean_ticks, std_dev
+        );
+    }
+}
+
+fn print_points(frames: &[Frame]) {
+    println!();
+    println!("Points:");
+    let mut score = 0;
+    for (tick, frame) in frames.iter().enumerate() {
+        if frame.score != score {
+            println!("  {:>4} at tick {} (item {}, {:.2}s)", frame.score, tick, frame.index, frame.elapsed);
+            score = frame.score;
+        }
+    }
+}
+
+/// Side view of the play: time goes right, height goes up. `o` is the bird (`^` when it jumped),
+/// `#` the pipes next to the bird, `X` where the game ended, and `*` below the axis a point scored
+fn print_timeline(frames: &[Frame]) {
+    if frames.is_empty() {
+        return;
+    }
+    let frames_per_column = frames.len().div_ceil(TIMELINE_COLUMNS);
+    let columns = frames.len().div_ceil(frames_per_column);
+    let ceiling = WINDOW_Y / 2;
+    let row_height = (ceiling - GROUND_Y) as f32 / TIMELINE_ROWS as f32;
+    let row = |y: i32| (((y - GROUND_Y) as f32 / row_height) as usize).min(TIMELINE_ROWS - 1);
+
+    let mut grid = vec![vec![' '; columns]; TIMELINE_ROWS];
+    let mut points = vec![' '; columns];
+    let mut score = 0;
+    for (column, chunk) in frames.chunks(frames_per_column).enumerate() {
+        let frame = chunk.last().unwrap();
+        for (i, pipe) in frame.pipes.iter().enumerate() {
+            if (pipe.x - frame.bird.x).abs() > (PIPE_WIDTH + BIRD_WIDTH) / 2. {
+                continue;
+            }
+            // even pipes are the lower ones
+            let (bottom, top) = if i % 2 == 0 {
+                (GROUND_Y, pipe.y + PIPE_HEIGHT / 2)
+            } else {
+                (pipe.y - PIPE_HEIGHT / 2, ceiling)
+            };
+            if top < GROUND_Y || bottom > ceiling {
+                continue;
+            }
+            for cells in grid.iter_mut().take(row(top) + 1).skip(row(bottom.max(GROUND_Y))) {
+                cells[column] = '#';
+            }
+        }
+        let bird = if chunk.iter().any(|frame| frame.collision) {
+            'X'
+        } else if chunk.iter().any(|frame| frame.jumps > 0) {
+            '^'
+        } else {
+            'o'
+        };
+        grid[row(frame.bird.y)][column] = bird;
+        if frame.score != score {
+            points[column] = '*';
+            score = frame.score;
+        }
+    }
+
+    println!();
+    println!("Timeline ({} frames per column):", frames_per_column);
+    for cells in grid.iter().rev() {
+        println!("|{}", cells.iter().collect::<String>());
+    }
+    println!("+{}", "-".repeat(columns));
+    println!(" {}", points.iter().collect::<String>());
+}
+
+const MAX_EXAMPLES: usize = 5;
+
+fn print_anomalies(items: &[TraceItem], error: Option<TraceError>) {
+    // description of the anomaly, and the items it was found at
+    let mut anomalies: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut found = |anomaly: String, example: String| anomalies.entry(anomaly).or_default().push(example);
+    if let Some(err) = error {
+        found(format!("the replay fails: {}", err), String::new());
+    }
+    let mut previous_tick: Option<&Action> = None;
+    let mut jumps_since_tick = 0;
+    for (index, item) in items.iter().enumerate() {
+        match item.action {
+            Action::ApplyPhysics | Action::CheckCollisionAndMovePipes => {
+                let delta = f32::from_le_bytes(item.data);
+                if !delta.is_finite() || !(MIN_DELTA..=MAX_DELTA).contains(&delta) {
+                    found(
+                        format!("{:?} delta outside of {}s..{}s", item.action, MIN_DELTA, MAX_DELTA),
+                        format!("item {} ({})", index, delta),
+                    );
+                }
+                if previous_tick == Some(&item.action) {
+                    found(format!("two {:?} in a row", item.action), format!("item {}", index));
+                }
+                previous_tick = Some(&item.action);
+                jumps_since_tick = 0;
+            }
+            Action::Jump => {
+                jumps_since_tick += 1;
+                if jumps_since_tick == 2 {
+                    found("several jumps in the same frame".to_string(), format!("item {}", index));
+                }
+                if item.data != [0; 4] {
+                    found("Jump with data".to_string(), format!("item {} ({:?})", index, item.data));
+                }
+            }
+            Action::GameOver if item.data != [0; 4] => {
+                found("GameOver with data".to_string(), format!("item {} ({:?})", index, item.data));
+            }
+            Action::GameOver | Action::NewPlay => {}
+        }
+    }
+
+    println!();
+    if anomalies.is_empty() {
+        println!("No anomalies");
+        return;
+    }
+    println!("Anomalies:");
+    for (anomaly, examples) in anomalies {
+        let examples: Vec<&String> = examples.iter().filter(|example| !example.is_empty()).collect();
+        if examples.is_empty() {
+            println!("  {}", anomaly);
+            continue;
+        }
+        let more = examples.len().saturating_sub(MAX_EXAMPLES);
+        let listed: Vec<&str> = examples.iter().take(MAX_EXAMPLES).map(|example| example.as_str()).collect();
+        print!("  {}: {} times, at {}", anomaly, examples.len(), listed.join(", "));
+        if more > 0 {
+            print!(" and {} more", more);
+        }
+        println!();
+    }
+}
+
+fn print_humanity(encoded_trace: &[u8]) {
+    println!();
+    let report = match heuristics::analyze(encoded_trace) {
+        Ok(report) => report,
+        Err(err) => {
+            println!("Humanity: can't be analyzed, {}", err);
+            return;
+        }
+    };
+    match report.score {
+        Some(score) => println!("Humanity score: {:.2} (0 is scripted, 1 is human)", score),
+        None => println!("Humanity score: unknown"),
+    }
+    if let Some(cv) = report.jump_interval_cv {
+        println!("  jump interval coefficient of variation: {:.3}", cv);
+    }
+    if let Some(reaction) = report.median_reaction_seconds {
+        println!("  median reaction to gap changes: {:.0}ms", reaction * 1000.);
+    }
+    if let Some(std_dev) = report.delta_std_dev {
+        println!("  delta std dev: {:.5}s", std_dev);
+    }
+    for reason in report.reasons.iter() {
+        println!("  {}", reason);
+    }
+}
@@ -0,0 +1,47 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::keccak256;
+use sp1_sdk::{ProverClient, SP1ProvingKey, SP1VerifyingKey, SP1_CIRCUIT_VERSION};
+
+/// Directory of the key cache, `KEY_CACHE_DIR` or `prover/keys` by default
+pub fn cache_dir() -> PathBuf {
+    match std::env::var_os("KEY_CACHE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../keys"),
+    }
+}
+
+/// Same as `client.setup(elf)`, but the keys are cached on disk, keyed by the SP1 version and the
+/// hash of the ELF. Keys of any other ELF or version are removed when the cache is refreshed
+pub fn setup(client: &ProverClient, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey) {
+    let dir = cache_dir();
+    // the keys of the same ELF change with the prover, and may not even deserialize
+    let path = dir.join(format!("{}-{:x}.bin", SP1_CIRCUIT_VERSION, keccak256(elf)));
+    if let Some(keys) = fs::read(&path).ok().and_then(|bytes| bincode::deserialize(&bytes).ok()) {
+        return keys;
+    }
+
+    println!("Setting up the proving key, it will be cached at {}", path.display());
+    let keys = client.setup(elf);
+    // failing to cache the keys isn't fatal, they'll be generated again next time
+    if let Err(err) = store(&dir, &path, &keys) {
+        println!("Failed to cache the keys: {}", err);
+    }
+    keys
+}
+
+fn store(dir: &Path, path: &Path, keys: &(SP1ProvingKey, SP1VerifyingKey)) -> eyre::Result<()> {
+    fs::create_dir_all(dir)?;
+    for entry in fs::read_dir(dir)? {
+        let stale = entry?.path();
+        if stale.extension().is_some_and(|extension| extension == "bin") && stale != path {
+            fs::remove_file(stale)?;
+        }
+    }
+    // write and rename, so concurrent runs never read a truncated file
+    let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp_path, bincode::serialize(keys)?)?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
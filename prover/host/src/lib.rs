@@ -0,0 +1,23 @@
+pub mod batch;
+pub mod fixture;
+pub mod formats;
+pub mod foundry;
+pub mod heuristics;
+pub mod inspect;
+pub mod keys;
+pub mod preflight;
+pub mod profile;
+pub mod segments;
+pub mod submit;
+pub mod tournament;
+
+/// Guest built from `prover/client` by `build.rs`, or the checked-in `elf/flazky-bird` when the
+/// SP1 toolchain isn't installed and it matches the guest sources
+pub const ELF_FLAZKY_BIRD: &[u8] = include_bytes!(env!("FLAZKY_BIRD_ELF"));
+
+/// Whether the build was allowed to use a checked-in ELF that doesn't match the guest sources
+/// (`FLAZKY_ALLOW_STALE_ELF`). The tests that run the guest are ignored then
+pub const STALE_ELF: bool = cfg!(stale_elf);
+
+/// Verifying key the Sepolia FlazkyBird contract was deployed with (`script/deployFlazky.s.sol`)
+pub const PINNED_VKEY: &str = "0x00d46e47dc204c9d22a872106269c07fab0001c502ff00a9c1480e4b056a89c5";
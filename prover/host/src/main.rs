@@ -1,40 +1,313 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf; 
-use clap::Parser;
+use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand};
 use sp1_sdk::{HashableKey, utils, ProverClient, SP1Stdin, SP1ProofWithPublicValues};
-use flazky_bird_lib::Input;
-use alloy::hex;
-use alloy_primitives::Address;
-use std::str::FromStr;
-use serde::{Serialize, Deserialize};
-use alloy_sol_types::SolType;
-use alloy_sol_types::sol;
+use flazky_bird_lib::{Input, Claim, Task, TournamentInput, segment_vkey_bytes, split_aggregate};
+use alloy_primitives::{Address, B256};
+
+use prover_host::{
+    batch, formats, foundry, heuristics, inspect, keys, preflight, profile, segments, submit, tournament,
+    ELF_FLAZKY_BIRD, PINNED_VKEY,
+};
+use prover_host::fixture::{ProofFixture, ProofType, proof_path, salt_path};
+use prover_host::formats::TraceFormat;
+use prover_contract::leaderboard;
+use prover_contract::FlazkyBird;
+use alloy::providers::ProviderBuilder;
 
 #[derive(Parser, Debug)]
-struct Args {
-    /// Whether or not to generate a proof.
-    #[arg(long, default_value_t = false)]
-    prove: bool,
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Execute the guest on a trace and report the score and cycles.
+    Execute(ExecuteArgs),
+    /// Execute the profiling guest on a trace and export the cycles spent in each part of it.
+    Profile(ProfileArgs),
+    /// Generate a proof of a trace and store it as a fixture.
+    Prove(ProveArgs),
+    /// Execute, and optionally prove, every trace of a directory or glob pattern.
+    Batch(BatchArgs),
+    /// Prove a set of traces and write the fixtures loaded by the contract tests.
+    ContractFixtures(ContractFixturesArgs),
+    /// Verify a fixture generated by `prove` with the SP1 verifier.
+    Verify(VerifyArgs),
+    /// Send the `addLeaderboardEntry` transaction of a fixture generated by `prove`.
+    Submit(SubmitArgs),
+    /// Print the on-chain leaderboard of a season.
+    Standings(StandingsArgs),
+    /// Decode a trace and print a summary of it.
+    Inspect(InspectArgs),
+    /// Convert a trace between bincode, JSON, NDJSON and the compact format.
+    Convert(ConvertArgs),
+    /// Prove tournament sessions and check tournament entries.
+    Tournament(TournamentArgs),
+    /// Print the verifying key of the program, and the one the contract checks for segments.
+    Vkey,
+    /// Check the verifying key of the program against the pinned one, and the on-chain one if an RPC URL is set.
+    CheckVkey(CheckVkeyArgs),
+}
+
+#[derive(Args, Debug)]
+struct TraceArgs {
     #[clap(long)]
     file: String,
 
     #[clap(long)]
-    eth_address: String,
+    eth_address: Address,
+
+    /// Season of the leaderboard the score is proven for.
+    #[clap(long, default_value_t = 0)]
+    season: u64,
+
+    /// Only prove that the score is at least this value, without revealing the exact score.
+    #[clap(long)]
+    threshold: Option<u32>,
+
+    /// Commit hash(player, salt) instead of the player address. The salt is stored next to the fixture.
+    #[arg(long, default_value_t = false)]
+    commit_player: bool,
+}
+
+#[derive(Args, Debug)]
+struct ExecuteArgs {
+    #[command(flatten)]
+    trace: TraceArgs,
+}
+
+#[derive(Args, Debug)]
+struct ProfileArgs {
+    #[command(flatten)]
+    trace: TraceArgs,
+
+    /// Where to store the JSON breakdown of the cycles.
+    #[clap(long, default_value_os_t = default_fixture_path().with_file_name("profile.json"))]
+    output: PathBuf,
+
+    /// Also profile the trace with the bincode decoder the guest used before streaming, and compare their cycle counts.
+    #[arg(long, default_value_t = false)]
+    compare_decoders: bool,
 }
 
+#[derive(Args, Debug)]
+struct ProveArgs {
+    #[command(flatten)]
+    trace: TraceArgs,
 
-sol! {
-    #[derive(Debug, Serialize, Deserialize)]
-    struct PublicValuesStruct {
-        address player;
-        uint256 score;
-        bytes32 nullifier;
+    /// Where to store the fixture. The full proof is stored next to it.
+    #[clap(long, default_value_os_t = default_fixture_path())]
+    output: PathBuf,
+
+    /// Proof system to use. Only plonk and groth16 proofs can be verified on-chain.
+    #[clap(long, value_enum, default_value_t = ProofType::Plonk)]
+    proof_type: ProofType,
+
+    /// Prove the trace in segments of this many items, chained together by a final recursive proof.
+    #[clap(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    segment_size: Option<usize>,
+
+    /// Refuse to prove traces whose humanity score (from 0, scripted, to 1, human) is lower than this.
+    #[clap(long)]
+    min_humanity: Option<f32>,
+
+    /// With `--min-humanity`, still prove traces too short to be scored.
+    #[clap(long, requires = "min_humanity")]
+    allow_unknown_humanity: bool,
+
+    #[command(flatten)]
+    chain: ChainArgs,
+}
+
+#[derive(Args, Debug)]
+struct ChainArgs {
+    /// RPC used to read the leaderboard. If set, `prove` stores the `_previousTokenID` to use in the fixture.
+    #[clap(long, env = "SEPOLIA_RPC_URL")]
+    rpc_url: Option<String>,
+
+    /// Address of the FlazkyBird contract, needed with an RPC URL.
+    #[clap(long, env = "FLAZKY_BIRD_ADDRESS")]
+    contract: Option<Address>,
+}
+
+impl ChainArgs {
+    fn contract(&self) -> eyre::Result<Address> {
+        self.contract.ok_or_else(|| eyre::eyre!("--contract (or FLAZKY_BIRD_ADDRESS) is needed to read the contract"))
     }
 }
 
+#[derive(Args, Debug)]
+struct BatchArgs {
+    /// Directory with the traces, or glob pattern matching them.
+    #[clap(long)]
+    traces: String,
+
+    #[clap(long)]
+    eth_address: Address,
+
+    /// Season of the leaderboard the scores are proven for.
+    #[clap(long, default_value_t = 0)]
+    season: u64,
+
+    /// Generate a proof for every trace, not only execute them.
+    #[arg(long, default_value_t = false)]
+    prove: bool,
+
+    #[clap(long, value_enum, default_value_t = ProofType::Plonk)]
+    proof_type: ProofType,
+
+    /// Number of traces processed at the same time.
+    #[clap(long, default_value_t = 1)]
+    concurrency: usize,
+
+    /// Directory where the fixtures are stored.
+    #[clap(long, default_value_os_t = default_fixture_path().with_file_name("batch"))]
+    output_dir: PathBuf,
+
+    /// Summary of the batch, as CSV if it ends with `.csv`, as JSON otherwise.
+    #[clap(long, default_value_os_t = default_fixture_path().with_file_name("batch/summary.json"))]
+    summary: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ContractFixturesArgs {
+    /// Directory with the traces, or glob pattern matching them.
+    #[clap(long)]
+    traces: String,
+
+    /// Players the traces are proven for, in turn.
+    #[clap(
+        long,
+        value_delimiter = ',',
+        default_values = [
+            "0x0000000000000000000000000000000000000001",
+            "0x0000000000000000000000000000000000000002",
+            "0x0000000000000000000000000000000000000003",
+        ]
+    )]
+    players: Vec<Address>,
+
+    /// Season all the entries are proven for.
+    #[clap(long, default_value_t = 0)]
+    season: u64,
+
+    #[clap(long, value_enum, default_value_t = ProofType::Plonk)]
+    proof_type: ProofType,
+
+    #[clap(long, default_value_os_t = foundry::default_output_path())]
+    output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct VerifyArgs {
+    /// Fixture generated by `prove`.
+    #[clap(long, default_value_os_t = default_fixture_path())]
+    fixture: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct SubmitArgs {
+    /// Fixture generated by `prove`.
+    #[clap(long, default_value_os_t = default_fixture_path())]
+    fixture: PathBuf,
+
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    /// Key of the account that sends the transaction.
+    #[clap(long, env = "PRIVATE_KEY", hide_env_values = true)]
+    private_key: String,
+}
+
+#[derive(Args, Debug)]
+struct TournamentArgs {
+    /// Tournament definition, as JSON: `{"id": 1, "seeds": [12, -3], "max_attempts": 3, "starts_at": 1735689600,
+    /// "ends_at": 1736294400}`, the window in unix seconds.
+    #[clap(long)]
+    tournament: PathBuf,
+
+    #[command(subcommand)]
+    command: TournamentCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum TournamentCommand {
+    /// Replay a session against the tournament and prove its aggregate score.
+    Prove {
+        /// Trace of every play of the session, in the order they were played.
+        #[clap(long, value_delimiter = ',', required = true)]
+        plays: Vec<PathBuf>,
+
+        #[clap(long)]
+        eth_address: Address,
+
+        #[clap(long, value_enum, default_value_t = ProofType::Plonk)]
+        proof_type: ProofType,
+
+        /// Where to store the fixture. The full proof is stored next to it.
+        #[clap(long, default_value_os_t = default_fixture_path().with_file_name("tournament.json"))]
+        output: PathBuf,
+    },
+    /// Check that entries were proven for this tournament, verify them and rank them.
+    Check {
+        /// Fixtures generated by `tournament prove`.
+        #[clap(long, value_delimiter = ',', required = true)]
+        entries: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct CheckVkeyArgs {
+    /// Verifying key to compare with, instead of the pinned one.
+    #[clap(long, default_value = PINNED_VKEY)]
+    expected: String,
+
+    #[command(flatten)]
+    chain: ChainArgs,
+}
+
+#[derive(Args, Debug)]
+struct StandingsArgs {
+    #[clap(long, default_value_t = 0)]
+    season: u64,
+
+    /// Print the standings as JSON.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+
+    #[command(flatten)]
+    chain: ChainArgs,
+}
+
+#[derive(Args, Debug)]
+struct InspectArgs {
+    #[clap(long)]
+    file: String,
+}
+
+#[derive(Args, Debug)]
+struct ConvertArgs {
+    #[clap(long)]
+    input: PathBuf,
+
+    #[clap(long)]
+    output: PathBuf,
+
+    /// Format of the input, guessed from its extension by default.
+    #[clap(long, value_enum)]
+    from: Option<TraceFormat>,
 
-const ELF_FLAZKY_BIRD: &[u8] = include_bytes!("../../../elf/flazky-bird");
+    /// Format of the output, guessed from its extension by default.
+    #[clap(long, value_enum)]
+    to: Option<TraceFormat>,
+}
+
+fn default_fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../fixtures/flazky.json")
+}
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
@@ -43,63 +316,331 @@ async fn main() -> eyre::Result<()> {
     utils::setup_logger();
 
     // Parse the command line arguments.
-    let args = Args::parse();
-    let trace_file = args.file;
-    let trace_data = fs::read(trace_file)?;
-    let eth_address: Address = Address::from_str(&args.eth_address).expect("Invalid address");
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Execute(args) => execute(args),
+        Command::Profile(args) => profile(args),
+        Command::Prove(args) => prove(args).await,
+        Command::Batch(args) => batch(args),
+        Command::ContractFixtures(args) => {
+            let traces = batch::find_traces(&args.traces)?;
+            let client = ProverClient::new();
+            let (pk, vk) = keys::setup(&client, ELF_FLAZKY_BIRD);
+            let fixtures =
+                foundry::generate(&client, &pk, &vk, &traces, &args.players, args.season, args.proof_type)?;
+            fixtures.save(&args.output)?;
+            println!("{} contract fixtures saved to {}", fixtures.count, args.output.display());
+            Ok(())
+        }
+        Command::Verify(args) => verify(args),
+        Command::Submit(args) => {
+            let fixture = ProofFixture::load(&args.fixture)?;
+            let rpc_url = args.chain.rpc_url.as_deref().ok_or_else(|| eyre::eyre!("--rpc-url is needed to submit"))?;
+            submit::submit(&fixture, rpc_url, args.chain.contract()?, &args.private_key).await?;
+            Ok(())
+        }
+        Command::Standings(args) => standings(args).await,
+        Command::Inspect(args) => inspect::inspect(&formats::read_encoded(args.file.as_ref())?),
+        Command::Convert(args) => {
+            let from = args.from.unwrap_or_else(|| TraceFormat::from_path(&args.input));
+            let to = args.to.unwrap_or_else(|| TraceFormat::from_path(&args.output));
+            let items = formats::decode(&fs::read(&args.input)?, from)?;
+            fs::write(&args.output, formats::encode(&items, to)?)?;
+            println!("Converted {} items from {:?} to {:?}", items.len(), from, to);
+            Ok(())
+        }
+        Command::Vkey => {
+            let client = ProverClient::new();
+            let (_, vk) = keys::setup(&client, ELF_FLAZKY_BIRD);
+            println!("{}", vk.bytes32());
+            println!("segments: {}", segment_vkey_bytes(vk.hash_u32()));
+            Ok(())
+        }
+        Command::CheckVkey(args) => check_vkey(args).await,
+        Command::Tournament(args) => run_tournament(args),
+    }
+}
 
+impl TraceArgs {
+    fn input(&self) -> eyre::Result<Input> {
+        let trace_data = formats::read_encoded(self.file.as_ref())?;
+        let claim = match self.threshold {
+            Some(threshold) => Claim::Threshold(threshold),
+            None => Claim::Score,
+        };
+        let salt = self.commit_player.then(|| B256::from(rand::random::<[u8; 32]>()));
+        Ok(Input {
+            encoded_trace: trace_data,
+            player: self.eth_address,
+            season: self.season,
+            claim,
+            salt,
+        })
+    }
+}
+
+fn execute(args: ExecuteArgs) -> eyre::Result<()> {
+    let input = args.trace.input()?;
+    let expected_score = preflight::preflight(&input)?;
     let client = ProverClient::new();
-    let (pk, vk) = client.setup(ELF_FLAZKY_BIRD);
     let mut stdin = SP1Stdin::new();
-    stdin.write(&Input{
-        encoded_trace: trace_data,
-        player: eth_address,
-    });
-    let (mut public_values, execution_report) =
-        client.execute(&pk.elf, stdin.clone()).run().unwrap();
+    stdin.write(&Task::Run(input.clone()));
+    let (public_values, execution_report) = client
+        .execute(ELF_FLAZKY_BIRD, stdin)
+        .run()
+        .map_err(|err| eyre::eyre!("execution failed: {}", err))?;
     println!(
         "Finished executing the block in {} cycles",
         execution_report.total_instruction_count()
     );
-    // let prover_high_score = public_values.read::<u32>();
-    // println!("Prover high score: {}", prover_high_score);
-
-    if args.prove {
-        println!("Starting proof generation.");
-        let proof: SP1ProofWithPublicValues = client.prove(&pk, stdin.clone()).plonk().run().expect("Proving should work.");
-        println!("Proof generation finished.");
-
-        // Handle the result of the save operation
-        let public_values_solidity_encoded = proof.public_values.as_slice();
-        let decoded_values = PublicValuesStruct::abi_decode(public_values_solidity_encoded, true).unwrap();
-        let fixture = ProofFixture {
-            player: decoded_values.player.to_string(),
-            score: decoded_values.score.to_string(),
-            nullifier: decoded_values.nullifier.to_string(),
-            vkey: vk.bytes32().to_string(),
-            public_values: format!("0x{}", hex::encode(public_values_solidity_encoded)),
-            proof: format!("0x{}", hex::encode(proof.bytes())),
-        };
+    preflight::check_committed_score(&input, expected_score, public_values.as_slice())?;
+    match input.claim {
+        Claim::Score => println!("Score: {}", expected_score),
+        Claim::Threshold(threshold) => {
+            println!("Score: {}, the guest committed the threshold {}", expected_score, threshold)
+        }
+    }
+    Ok(())
+}
+
+fn profile(args: ProfileArgs) -> eyre::Result<()> {
+    let input = args.trace.input()?;
+    preflight::preflight(&input)?;
+    let client = ProverClient::new();
+    let (cycle_profile, bincode) = if args.compare_decoders {
+        let (streaming, bincode) = profile::compare_decoders(&client, &input)?;
+        (streaming, Some(bincode))
+    } else {
+        (profile::profile(&client, &input)?, None)
+    };
+    cycle_profile.print();
+    cycle_profile.save(&args.output)?;
+    println!("Cycle profile saved to {}", args.output.display());
 
-        let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../fixtures");
-        std::fs::create_dir_all(&fixture_path).expect("failed to create fixture path");
-        std::fs::write(
-            fixture_path.join(format!("flazky.json").to_lowercase()),
-            serde_json::to_string_pretty(&fixture).unwrap(),
-        )
-        .expect("failed to write fixture");
-        println!("proof generated, output stored at ../fixtures/flazky.json")
+    if let Some(bincode) = bincode {
+        for (name, streaming_cycles, bincode_cycles) in [
+            ("Total", cycle_profile.total_cycles, bincode.total_cycles),
+            ("Deserialize", cycle_profile.span("deserialize"), bincode.span("deserialize")),
+            ("Hash", cycle_profile.span("hash"), bincode.span("hash")),
+        ] {
+            println!(
+                "{}: {} cycles streaming, {} cycles with bincode ({:+.2}%)",
+                name,
+                streaming_cycles,
+                bincode_cycles,
+                (streaming_cycles as f64 - bincode_cycles as f64) * 100. / bincode_cycles.max(1) as f64
+            );
+        }
     }
+    Ok(())
+}
+
+async fn prove(args: ProveArgs) -> eyre::Result<()> {
+    let input = args.trace.input()?;
+    let expected_score = preflight::preflight(&input)?;
+    if let Some(min_humanity) = args.min_humanity {
+        let report = heuristics::check(&input.encoded_trace, min_humanity, args.allow_unknown_humanity)?;
+        match report.score {
+            Some(score) => println!("Humanity score: {:.2}", score),
+            None => println!("Humanity score: unknown, the trace is too short to be scored"),
+        }
+    }
+    // only score proofs can be added to the leaderboard, threshold proofs can't be ranked
+    let leaderboard_contract = match &args.chain.rpc_url {
+        Some(rpc_url) if input.claim == Claim::Score => {
+            let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
+            let contract = FlazkyBird::new(args.chain.contract()?, provider);
+            // don't spend a proof on a season the contract won't accept
+            leaderboard::check_season(&contract, input.season).await?;
+            Some(contract)
+        }
+        _ => None,
+    };
+    let client = ProverClient::new();
+    let (pk, vk) = keys::setup(&client, ELF_FLAZKY_BIRD);
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&Task::Run(input.clone()));
+
+    println!("Starting {:?} proof generation.", args.proof_type);
+    let proof: SP1ProofWithPublicValues = match args.segment_size {
+        Some(segment_size) => segments::prove_segmented(&client, &pk, &vk, &input, segment_size, args.proof_type)?,
+        None => args.proof_type.prove(&client, &pk, stdin)?,
+    };
+    println!("Proof generation finished.");
+    preflight::check_committed_score(&input, expected_score, proof.public_values.as_slice())?;
 
+    let mut fixture = ProofFixture::new(&proof, args.proof_type, &vk, &input);
+    if let Some(contract) = &leaderboard_contract {
+        let previous_token_id = leaderboard::previous_token_id(contract, input.season, expected_score as u64).await?;
+        println!("Previous token ID in the leaderboard: {}", previous_token_id);
+        fixture.previous_token_id = Some(previous_token_id.to_string());
+    }
+    fixture.save(&args.output, &proof, &input)?;
+    println!("proof generated, output stored at {}", args.output.display());
+    if input.salt.is_some() {
+        println!(
+            "player salt stored at {}, keep it private until you claim the score with `claim(salt, recipient)` from the player address",
+            salt_path(&args.output).display()
+        );
+    }
     Ok(())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ProofFixture {
-    pub player: String,
-    pub score: String,
-    pub nullifier: String,
-    pub public_values: String,
-    pub proof: String,
-    pub vkey: String,
-}  
+fn batch(args: BatchArgs) -> eyre::Result<()> {
+    let traces = batch::find_traces(&args.traces)?;
+    eyre::ensure!(!traces.is_empty(), "no traces found at {}", args.traces);
+    fs::create_dir_all(&args.output_dir)?;
+
+    let client = ProverClient::new();
+    let (pk, vk) = keys::setup(&client, ELF_FLAZKY_BIRD);
+    let options = batch::BatchOptions {
+        proof_type: args.prove.then_some(args.proof_type),
+        season: args.season,
+        concurrency: args.concurrency,
+        output_dir: args.output_dir,
+    };
+    let records = batch::run_batch(&client, &pk, &vk, &traces, args.eth_address, &options);
+
+    if let Some(dir) = args.summary.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    batch::write_summary(&args.summary, &records)?;
+    let failures = records.iter().filter(|record| !record.success).count();
+    println!(
+        "{} traces, {} succeeded, {} failed. Summary stored at {}",
+        records.len(),
+        records.len() - failures,
+        failures,
+        args.summary.display()
+    );
+    eyre::ensure!(failures == 0, "{} of {} traces failed", failures, records.len());
+    Ok(())
+}
+
+async fn standings(args: StandingsArgs) -> eyre::Result<()> {
+    let rpc_url = args.chain.rpc_url.as_deref().ok_or_else(|| eyre::eyre!("--rpc-url is needed to read the leaderboard"))?;
+    let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
+    let contract = FlazkyBird::new(args.chain.contract()?, provider);
+    let standings = leaderboard::standings(&contract, args.season).await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&standings)?);
+        return Ok(());
+    }
+    println!("Season {}: {} entries", args.season, standings.len());
+    for standing in standings.iter() {
+        println!("{:>4}. {:>6} {} (token ID {})", standing.rank, standing.score, standing.owner, standing.token_id);
+    }
+    Ok(())
+}
+
+async fn check_vkey(args: CheckVkeyArgs) -> eyre::Result<()> {
+    let client = ProverClient::new();
+    let (_, vk) = keys::setup(&client, ELF_FLAZKY_BIRD);
+    let vkey = vk.bytes32();
+    println!("Program vkey: {}", vkey);
+
+    eyre::ensure!(
+        vkey.eq_ignore_ascii_case(&args.expected),
+        "vkey drift: the program vkey is {}, but {} was expected. The ELF doesn't match the pinned program",
+        vkey,
+        args.expected
+    );
+    println!("Matches the expected vkey.");
+
+    if let Some(rpc_url) = &args.chain.rpc_url {
+        let address = args.chain.contract()?;
+        let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
+        let contract = FlazkyBird::new(address, provider);
+        let onchain_vkey = contract.vKey().call().await?._0.to_string();
+        eyre::ensure!(
+            vkey.eq_ignore_ascii_case(&onchain_vkey),
+            "vkey drift: the program vkey is {}, but the contract at {} was deployed with {}",
+            vkey,
+            address,
+            onchain_vkey
+        );
+        println!("Matches the vkey of the contract at {}.", address);
+    }
+    Ok(())
+}
+
+fn run_tournament(args: TournamentArgs) -> eyre::Result<()> {
+    let definition = tournament::load_tournament(&args.tournament)?;
+    let client = ProverClient::new();
+    let (pk, vk) = keys::setup(&client, ELF_FLAZKY_BIRD);
+    match args.command {
+        TournamentCommand::Prove { plays, eth_address, proof_type, output } => {
+            let input = TournamentInput {
+                tournament: definition,
+                encoded_session: tournament::encode_session(&plays)?,
+                player: eth_address,
+            };
+            let (proof, fixture) = tournament::prove(&client, &pk, &vk, &input, proof_type)?;
+            fixture.save(&output, &proof)?;
+            println!("tournament proof generated, output stored at {}", output.display());
+        }
+        TournamentCommand::Check { entries } => {
+            let mut standings = Vec::new();
+            // the same session can only be entered once, whoever it was proven for
+            let mut nullifiers: HashMap<B256, &PathBuf> = HashMap::new();
+            for entry in entries.iter() {
+                match tournament::check_entry(&client, &vk, &definition, entry) {
+                    Ok(public_values) => match nullifiers.get(&public_values.nullifier) {
+                        Some(first) => println!("{}: rejected, same session as {}", entry.display(), first.display()),
+                        None => {
+                            nullifiers.insert(public_values.nullifier, entry);
+                            standings.push((public_values.score, public_values.player, entry));
+                        }
+                    },
+                    Err(err) => println!("{}: rejected, {}", entry.display(), err),
+                }
+            }
+            standings.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+            if definition.is_open(tournament::now()) {
+                println!("Tournament {} is still open, the standings aren't final", definition.id);
+            }
+            println!("Tournament {} standings:", definition.id);
+            for (rank, (score, player, entry)) in standings.iter().enumerate() {
+                println!("{:>4}. {} {} ({})", rank + 1, player, score, entry.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn verify(args: VerifyArgs) -> eyre::Result<()> {
+    let fixture = ProofFixture::load(&args.fixture)?;
+    let proof = SP1ProofWithPublicValues::load(proof_path(&args.fixture))
+        .map_err(|err| eyre::eyre!("failed to load the proof of {}: {}", args.fixture.display(), err))?;
+    let client = ProverClient::new();
+    let (_, vk) = keys::setup(&client, ELF_FLAZKY_BIRD);
+
+    eyre::ensure!(fixture.vkey == vk.bytes32(), "fixture was generated with vkey {}, the program vkey is {}", fixture.vkey, vk.bytes32());
+    eyre::ensure!(
+        fixture.public_values == format!("0x{}", alloy::hex::encode(proof.public_values.as_slice())),
+        "fixture public values don't match the proof"
+    );
+    // the fixture proof is what gets submitted on-chain, it must be the proof that is verified here
+    let proof_bytes = if fixture.proof_type.is_onchain() { proof.bytes() } else { Vec::new() };
+    eyre::ensure!(
+        fixture.proof == format!("0x{}", alloy::hex::encode(proof_bytes)),
+        "fixture proof doesn't match the {:?} proof stored next to it",
+        fixture.proof_type
+    );
+    if let Some((segment_vkey, _)) = split_aggregate(proof.public_values.as_slice()) {
+        let expected = segment_vkey_bytes(vk.hash_u32());
+        eyre::ensure!(
+            segment_vkey == expected,
+            "segments were proven with vkey {}, the program segment vkey is {}",
+            segment_vkey,
+            expected
+        );
+        eyre::ensure!(
+            fixture.segment_vkey == Some(segment_vkey.to_string()),
+            "fixture segment vkey doesn't match the proof"
+        );
+    }
+    client.verify(&proof, &vk).map_err(|err| eyre::eyre!("proof verification failed: {}", err))?;
+    println!("{:?} proof verified successfully.", fixture.proof_type);
+    Ok(())
+}
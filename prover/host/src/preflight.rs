@@ -0,0 +1,69 @@
+use alloy_primitives::U256;
+use eyre::{bail, ensure};
+use flazky_bird_lib::{replay_encoded, split_aggregate, Action, Claim, Input, TraceDecoder, TraceError};
+
+/// Replays the trace natively before running it in the zkVM, returning the expected score
+pub fn preflight(input: &Input) -> eyre::Result<u32> {
+    let score = match replay_encoded(&input.encoded_trace, |_| {}) {
+        Ok(game) => game.score(),
+        Err(err) => bail!("invalid trace: {}{}", err, describe(&input.encoded_trace, &err)),
+    };
+    println!("Native replay score: {}", score);
+    if let Claim::Threshold(threshold) = input.claim {
+        ensure!(score >= threshold, "score {} is lower than the threshold {}", score, threshold);
+    }
+    Ok(score)
+}
+
+/// Checks the score committed by the guest matches the native replay, or for a threshold claim
+/// that the guest committed the threshold it was asked for
+pub fn check_committed_score(input: &Input, expected_score: u32, public_values: &[u8]) -> eyre::Result<()> {
+    // aggregated proofs commit the verifying key of the segments first
+    let public_values = split_aggregate(public_values).map_or(public_values, |(_, run)| run);
+    ensure!(public_values.len() >= 128, "guest committed {} bytes of public values", public_values.len());
+    // the score or threshold is the fourth word of the public values, after the kind, the player and the season
+    let committed = U256::from_be_slice(&public_values[96..128]);
+    match input.claim {
+        Claim::Score => ensure!(
+            committed == U256::from(expected_score),
+            "guest committed score {}, but the native replay scored {}",
+            committed,
+            expected_score
+        ),
+        Claim::Threshold(threshold) => ensure!(
+            committed == U256::from(threshold),
+            "guest committed threshold {}, but {} was claimed",
+            committed,
+            threshold
+        ),
+    }
+    Ok(())
+}
+
+/// Details about the item that made the trace invalid
+fn describe(encoded_trace: &[u8], err: &TraceError) -> String {
+    let Some(index) = err.index() else {
+        return String::new();
+    };
+    let Ok(decoder) = TraceDecoder::new(encoded_trace) else {
+        return String::new();
+    };
+    let mut elapsed = 0.;
+    for (i, item) in decoder.enumerate() {
+        let Ok(item) = item else { break };
+        if i == index {
+            let data = match item.action {
+                Action::NewPlay => format!("seed {}", i32::from_le_bytes(item.data)),
+                Action::ApplyPhysics | Action::CheckCollisionAndMovePipes => {
+                    format!("delta {}", f32::from_le_bytes(item.data))
+                }
+                Action::Jump | Action::GameOver => format!("data {:?}", item.data),
+            };
+            return format!(" ({:?}, {}, {:.2}s into the play)", item.action, data, elapsed);
+        }
+        if item.action == Action::ApplyPhysics {
+            elapsed += f32::from_le_bytes(item.data);
+        }
+    }
+    String::new()
+}
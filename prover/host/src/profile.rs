@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use flazky_bird_lib::{DecodeStrategy, Input, Task, TraceDecoder};
+use serde::Serialize;
+use sp1_sdk::{ProverClient, SP1PublicValues, SP1Stdin};
+
+/// Guest built with the `profile` feature, which reports cycle tracking spans. Only built along
+/// with the guest when the SP1 toolchain is installed
+pub fn profile_elf_path() -> PathBuf {
+    PathBuf::from(env!("FLAZKY_BIRD_PROFILE_ELF"))
+}
+
+/// Cycles spent by the guest on a trace, per cycle tracking span
+#[derive(Debug, Serialize)]
+pub struct CycleProfile {
+    /// Commit of the repository the guest was built from, if known
+    pub commit: Option<String>,
+    pub decode_strategy: DecodeStrategy,
+    pub items: usize,
+    pub total_cycles: u64,
+    /// Cycles of every span (`read-input`, `deserialize`, `hash`, `abi-encode`, `commit`), summed
+    /// over all the times it was entered
+    pub spans: BTreeMap<String, u64>,
+    /// Cycles of the handler of every action, and how many items of that action the trace has
+    pub actions: BTreeMap<String, ActionProfile>,
+    /// Cycles outside of any span, including the cost of the tracking itself
+    pub untracked_cycles: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActionProfile {
+    pub items: usize,
+    pub cycles: u64,
+    pub cycles_per_item: f64,
+}
+
+/// Executes the profiling guest on a trace and collects the cycles of its spans
+pub fn profile(client: &ProverClient, input: &Input) -> eyre::Result<CycleProfile> {
+    Ok(run(client, input, DecodeStrategy::Streaming)?.0)
+}
+
+/// Profiles the trace with the streaming decoder of the guest and with the bincode one it replaced,
+/// checking that both commit the same public values
+pub fn compare_decoders(client: &ProverClient, input: &Input) -> eyre::Result<(CycleProfile, CycleProfile)> {
+    let (streaming, streaming_public_values) = run(client, input, DecodeStrategy::Streaming)?;
+    let (bincode, bincode_public_values) = run(client, input, DecodeStrategy::Bincode)?;
+    eyre::ensure!(
+        streaming_public_values.as_slice() == bincode_public_values.as_slice(),
+        "both decoders should commit the same public values"
+    );
+    Ok((streaming, bincode))
+}
+
+fn run(
+    client: &ProverClient,
+    input: &Input,
+    decode_strategy: DecodeStrategy,
+) -> eyre::Result<(CycleProfile, SP1PublicValues)> {
+    let elf_path = profile_elf_path();
+    let elf = fs::read(&elf_path).map_err(|err| {
+        eyre::eyre!(
+            "can't read {} ({}), it's built with the host when the SP1 toolchain is installed",
+            elf_path.display(),
+            err
+        )
+    })?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&Task::Run(input.clone()));
+    // only read by the profiling build
+    stdin.write(&decode_strategy);
+    let (public_values, report) =
+        client.execute(&elf, stdin).run().map_err(|err| eyre::eyre!("execution failed: {}", err))?;
+
+    let mut counts = BTreeMap::new();
+    let mut items = 0;
+    for item in TraceDecoder::new(&input.encoded_trace)? {
+        *counts.entry(format!("{:?}", item?.action)).or_insert(0) += 1;
+        items += 1;
+    }
+
+    let mut spans = BTreeMap::new();
+    let mut actions = BTreeMap::new();
+    for (name, cycles) in report.cycle_tracker.iter() {
+        match name.strip_prefix("action:") {
+            Some(action) => {
+                let items = counts.get(action).copied().unwrap_or(0);
+                actions.insert(
+                    action.to_string(),
+                    ActionProfile { items, cycles: *cycles, cycles_per_item: *cycles as f64 / items.max(1) as f64 },
+                );
+            }
+            None => {
+                spans.insert(name.clone(), *cycles);
+            }
+        }
+    }
+    let total_cycles = report.total_instruction_count();
+    let tracked: u64 = report.cycle_tracker.values().sum();
+    let profile = CycleProfile {
+        commit: git_commit(),
+        decode_strategy,
+        items,
+        total_cycles,
+        spans,
+        actions,
+        untracked_cycles: total_cycles.saturating_sub(tracked),
+    };
+    Ok((profile, public_values))
+}
+
+impl CycleProfile {
+    pub fn print(&self) {
+        println!("Total: {} cycles over {} items", self.total_cycles, self.items);
+        for (name, cycles) in self.spans.iter() {
+            println!("{}: {} cycles ({:.2}%)", name, cycles, self.percent(*cycles));
+        }
+        for (name, action) in self.actions.iter() {
+            println!(
+                "{}: {} cycles ({:.2}%), {} items, {:.1} cycles per item",
+                name,
+                action.cycles,
+                self.percent(action.cycles),
+                action.items,
+                action.cycles_per_item
+            );
+        }
+        println!("Untracked: {} cycles ({:.2}%)", self.untracked_cycles, self.percent(self.untracked_cycles));
+    }
+
+    /// Cycles of a span, 0 if it was never entered
+    pub fn span(&self, name: &str) -> u64 {
+        self.spans.get(name).copied().unwrap_or(0)
+    }
+
+    fn percent(&self, cycles: u64) -> f64 {
+        cycles as f64 * 100. / self.total_cycles.max(1) as f64
+    }
+
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn git_commit() -> Option<String> {
+    let output = Command::new("git").args(["rev-parse", "HEAD"]).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,57 @@
+use flazky_bird_lib::{AggregateInput, Input, Replay, SegmentInput, SegmentSummary, Task, TraceDecoder};
+use sp1_sdk::{HashableKey, ProverClient, SP1Proof, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+
+use crate::fixture::ProofType;
+
+/// Splits the trace in segments, proves each of them on its own and chains them with a final
+/// proof of type `proof_type`. Segments are always proven with compressed proofs
+pub fn prove_segmented(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    vk: &SP1VerifyingKey,
+    input: &Input,
+    segment_size: usize,
+    proof_type: ProofType,
+) -> eyre::Result<SP1ProofWithPublicValues> {
+    eyre::ensure!(segment_size > 0, "segments must have at least one item");
+    let mut decoder = TraceDecoder::new(&input.encoded_trace)?;
+    let mut replay = Replay::new(decoder.len())?;
+    let mut segments = Vec::new();
+    let mut segment_proofs = Vec::new();
+    loop {
+        let state = replay.clone();
+        let mut encoded_items = Vec::new();
+        let mut items = 0;
+        while items < segment_size {
+            let Some(item) = decoder.next_raw() else { break };
+            let (item, raw) = item?;
+            replay.step(&item)?;
+            encoded_items.extend_from_slice(raw);
+            items += 1;
+        }
+        if items == 0 {
+            break;
+        }
+
+        println!("Proving segment {} ({} items).", segments.len(), items);
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&Task::Segment(SegmentInput { state, encoded_items }));
+        let proof = ProofType::Compressed.prove(client, pk, stdin)?;
+        segments.push(SegmentSummary { items: items as u32, end_state: replay.state_hash() });
+        segment_proofs.push(proof);
+    }
+
+    println!("Aggregating {} segments.", segments.len());
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&Task::Aggregate(AggregateInput {
+        run: input.clone(),
+        segment_vkey: vk.hash_u32(),
+        segments,
+        final_state: replay,
+    }));
+    for proof in segment_proofs {
+        let SP1Proof::Compressed(proof) = proof.proof else { unreachable!() };
+        stdin.write_proof(proof, vk.vk.clone());
+    }
+    proof_type.prove(client, pk, stdin)
+}
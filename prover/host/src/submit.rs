@@ -0,0 +1,69 @@
+use alloy::hex;
+use alloy::network::EthereumWallet;
+use alloy::providers::ProviderBuilder;
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::{Bytes, U256};
+use eyre::{ensure, eyre};
+
+use prover_contract::leaderboard;
+use prover_contract::FlazkyBird;
+
+use crate::fixture::ProofFixture;
+
+/// Sends the `addLeaderboardEntry` transaction of a fixture, waits for it to be mined and returns
+/// the ID of the minted token
+pub async fn submit(fixture: &ProofFixture, rpc_url: &str, contract: alloy_primitives::Address, private_key: &str) -> eyre::Result<U256> {
+    // other proofs have no on-chain bytes, the verifier would reject them after paying for the transaction
+    ensure!(
+        fixture.proof_type.is_onchain(),
+        "{:?} proofs can't be verified on-chain, prove the trace with --proof-type plonk or groth16",
+        fixture.proof_type
+    );
+    let score: u64 = fixture
+        .score
+        .as_ref()
+        .ok_or_else(|| eyre!("only score proofs can be added to the leaderboard"))?
+        .parse()?;
+    let public_values = Bytes::from(hex::decode(&fixture.public_values)?);
+    let proof = Bytes::from(hex::decode(&fixture.proof)?);
+
+    let signer: PrivateKeySigner = private_key.parse()?;
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(signer))
+        .on_http(rpc_url.parse()?);
+    let contract = FlazkyBird::new(contract, provider);
+
+    leaderboard::check_season(&contract, fixture.season).await?;
+    ensure!(
+        !contract.nullifierMap(fixture.nullifier.parse()?).call().await?._0,
+        "nullifier {} was already used",
+        fixture.nullifier
+    );
+    // the leaderboard may have changed since the fixture was generated
+    let previous_token_id = leaderboard::previous_token_id(&contract, fixture.season, score).await?;
+    println!("Submitting score {} to season {} after token ID {}.", score, fixture.season, previous_token_id);
+
+    let receipt = contract
+        .addLeaderboardEntry(public_values, proof, previous_token_id)
+        .send()
+        .await?
+        .get_receipt()
+        .await?;
+    ensure!(receipt.status(), "transaction {} reverted", receipt.transaction_hash);
+    println!("Transaction {} included in block {:?}.", receipt.transaction_hash, receipt.block_number);
+
+    let token_id: U256 = receipt
+        .inner
+        .logs()
+        .iter()
+        .find_map(|log| log.log_decode::<FlazkyBird::Transfer>().ok())
+        .map(|transfer| transfer.inner.data.tokenId)
+        .ok_or_else(|| eyre!("no token was minted"))?;
+    println!("Minted token ID: {}", token_id);
+    match leaderboard::rank(&contract, fixture.season, token_id).await? {
+        Some(rank) => println!("Season {} leaderboard rank: {}", fixture.season, rank),
+        None => println!("Token {} not found in the leaderboard", token_id),
+    }
+    Ok(token_id)
+}
@@ -0,0 +1,169 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::hex;
+use alloy_primitives::U256;
+use alloy_sol_types::SolType;
+use flazky_bird_lib::{PublicValuesKind, Task, Tournament, TournamentInput, TournamentPublicValuesStruct, TraceItem};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+
+use crate::fixture::{proof_path, ProofType};
+use crate::formats::{self, TraceFormat};
+
+/// Proof of a tournament session, the tournament counterpart of `ProofFixture`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentFixture {
+    pub proof_type: ProofType,
+    pub player: String,
+    pub tournament_id: u64,
+    pub tournament_hash: String,
+    pub score: String,
+    pub nullifier: String,
+    pub public_values: String,
+    /// Empty for proofs that can't be verified on-chain, the full proof is always stored in `proof_path`
+    pub proof: String,
+    pub vkey: String,
+    /// Unix time, in seconds, the session was proven at. Reported by the entrant, the proof doesn't cover it
+    pub proven_at: u64,
+}
+
+/// Reads a tournament definition, stored as JSON
+pub fn load_tournament(path: &Path) -> eyre::Result<Tournament> {
+    let tournament: Tournament = serde_json::from_slice(&fs::read(path)?)?;
+    eyre::ensure!(
+        tournament.starts_at < tournament.ends_at,
+        "tournament {} ends before it starts",
+        tournament.id
+    );
+    Ok(tournament)
+}
+
+/// Current unix time, in seconds
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// Builds a session from one trace per play, in the order they were played
+pub fn encode_session(plays: &[PathBuf]) -> eyre::Result<Vec<u8>> {
+    let plays = plays
+        .iter()
+        .map(|play| formats::decode(&fs::read(play)?, TraceFormat::from_path(play)))
+        .collect::<eyre::Result<Vec<Vec<TraceItem>>>>()?;
+    Ok(bincode::serialize(&plays)?)
+}
+
+/// Replays the session natively against the tournament, returning the aggregate score
+pub fn preflight(tournament: &Tournament, encoded_session: &[u8]) -> eyre::Result<u32> {
+    let result = tournament.replay_encoded_session(encoded_session)?;
+    println!("Best scores: {:?}", result.best_scores);
+    println!("Native replay tournament score: {}", result.score);
+    Ok(result.score)
+}
+
+pub fn prove(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    vk: &SP1VerifyingKey,
+    input: &TournamentInput,
+    proof_type: ProofType,
+) -> eyre::Result<(SP1ProofWithPublicValues, TournamentFixture)> {
+    let proven_at = now();
+    eyre::ensure!(
+        input.tournament.is_open(proven_at),
+        "tournament {} only accepts entries from {} to {}",
+        input.tournament.id,
+        input.tournament.starts_at,
+        input.tournament.ends_at
+    );
+    let expected_score = preflight(&input.tournament, &input.encoded_session)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&Task::Tournament(input.clone()));
+    let proof = proof_type.prove(client, pk, stdin)?;
+
+    let public_values = decode_public_values(proof.public_values.as_slice())?;
+    eyre::ensure!(
+        public_values.score == U256::from(expected_score),
+        "guest committed score {}, but the native replay scored {}",
+        public_values.score,
+        expected_score
+    );
+    let proof_bytes = if proof_type.is_onchain() { proof.bytes() } else { Vec::new() };
+    let fixture = TournamentFixture {
+        proof_type,
+        player: public_values.player.to_string(),
+        tournament_id: public_values.tournamentId,
+        tournament_hash: public_values.tournamentHash.to_string(),
+        score: public_values.score.to_string(),
+        nullifier: public_values.nullifier.to_string(),
+        public_values: format!("0x{}", hex::encode(proof.public_values.as_slice())),
+        proof: format!("0x{}", hex::encode(proof_bytes)),
+        vkey: vk.bytes32(),
+        proven_at,
+    };
+    Ok((proof, fixture))
+}
+
+/// Checks an entry was proven for the local tournament definition during its window, and verifies
+/// its proof. Returns the public values of the entry
+pub fn check_entry(
+    client: &ProverClient,
+    vk: &SP1VerifyingKey,
+    tournament: &Tournament,
+    fixture_path: &Path,
+) -> eyre::Result<TournamentPublicValuesStruct> {
+    let fixture: TournamentFixture = serde_json::from_slice(&fs::read(fixture_path)?)?;
+    // the proof can't tell when the session was played, this only catches entries proven late
+    eyre::ensure!(
+        tournament.is_open(fixture.proven_at),
+        "entry was proven at {}, outside the window of tournament {} ({} to {})",
+        fixture.proven_at,
+        tournament.id,
+        tournament.starts_at,
+        tournament.ends_at
+    );
+    let proof = SP1ProofWithPublicValues::load(proof_path(fixture_path))
+        .map_err(|err| eyre::eyre!("failed to load the proof: {}", err))?;
+    let public_values = decode_public_values(proof.public_values.as_slice())?;
+
+    eyre::ensure!(
+        public_values.tournamentId == tournament.id,
+        "entry is for tournament {}, not {}",
+        public_values.tournamentId,
+        tournament.id
+    );
+    eyre::ensure!(
+        public_values.tournamentHash == tournament.hash(),
+        "entry was played with other seeds, attempts or window than tournament {}",
+        tournament.id
+    );
+    eyre::ensure!(
+        fixture.public_values == format!("0x{}", hex::encode(proof.public_values.as_slice())),
+        "fixture public values don't match the proof"
+    );
+    client.verify(&proof, vk).map_err(|err| eyre::eyre!("proof verification failed: {}", err))?;
+    Ok(public_values)
+}
+
+/// Decodes the public values of a tournament proof, refusing the ones of a run
+fn decode_public_values(public_values: &[u8]) -> eyre::Result<TournamentPublicValuesStruct> {
+    let decoded = TournamentPublicValuesStruct::abi_decode(public_values, true)?;
+    eyre::ensure!(
+        decoded.kind == PublicValuesKind::Tournament as u8,
+        "public values of kind {} aren't a tournament entry",
+        decoded.kind
+    );
+    Ok(decoded)
+}
+
+impl TournamentFixture {
+    pub fn save(&self, path: &Path, proof: &SP1ProofWithPublicValues) -> eyre::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        proof.save(proof_path(path)).map_err(|err| eyre::eyre!("failed to save the proof: {}", err))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+//! End-to-end checks of the golden traces of `logic/tests/traces`: native replay, guest execution,
+//! mock proving and, with `--ignored`, the leaderboard of `FlazkyBird.sol` deployed on anvil
+
+use std::fs;
+use std::path::PathBuf;
+
+use alloy::network::EthereumWallet;
+use alloy::node_bindings::Anvil;
+use alloy::providers::ProviderBuilder;
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::SolType;
+use flazky_bird_lib::{Claim, Input, PublicValuesKind, PublicValuesStruct, Task};
+use prover_contract::{deploy, leaderboard, FlazkyBird};
+use prover_host::fixture::{ProofFixture, ProofType};
+use prover_host::{preflight, submit, ELF_FLAZKY_BIRD};
+use serde::Deserialize;
+use sp1_sdk::{HashableKey, ProverClient, SP1Stdin};
+
+const PLAYER: &str = "0x00000000000000000000000000000000000f1a2b";
+// not the default season, so the leaderboard test checks seasons are kept apart
+const SEASON: u64 = 3;
+
+/// Expected values of a trace of `logic/tests/traces`, listed in `logic/tests/golden.json`
+#[derive(Debug, Deserialize)]
+struct Golden {
+    file: String,
+    score: u32,
+    nullifier: B256,
+}
+
+fn traces_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../logic/tests/traces")
+}
+
+fn golden_inputs() -> Vec<(Golden, Input)> {
+    let golden_path = traces_dir().with_file_name("golden.json");
+    let golden: Vec<Golden> = serde_json::from_slice(&fs::read(golden_path).unwrap()).unwrap();
+    golden
+        .into_iter()
+        .map(|golden| {
+            let input = Input {
+                encoded_trace: fs::read(traces_dir().join(&golden.file)).unwrap(),
+                player: PLAYER.parse().unwrap(),
+                season: SEASON,
+                claim: Claim::Score,
+                salt: None,
+            };
+            (golden, input)
+        })
+        .collect()
+}
+
+/// Mock proves every golden trace, checking each fixture against the golden values
+fn mock_fixtures() -> Vec<(Golden, ProofFixture)> {
+    let client = ProverClient::mock();
+    let (pk, vk) = client.setup(ELF_FLAZKY_BIRD);
+    golden_inputs()
+        .into_iter()
+        .map(|(golden, input)| {
+            let mut stdin = SP1Stdin::new();
+            stdin.write(&Task::Run(input.clone()));
+            let proof = ProofType::Plonk.prove(&client, &pk, stdin).unwrap();
+            client.verify(&proof, &vk).unwrap();
+
+            let fixture = ProofFixture::new(&proof, ProofType::Plonk, &vk, &input);
+            assert_eq!(fixture.score, Some(golden.score.to_string()), "{}", golden.file);
+            assert_eq!(fixture.nullifier, golden.nullifier.to_string(), "{}", golden.file);
+            assert_eq!(fixture.season, SEASON, "{}", golden.file);
+            assert_eq!(fixture.vkey, vk.bytes32(), "{}", golden.file);
+            (golden, fixture)
+        })
+        .collect()
+}
+
+#[test]
+fn native_replay_matches_golden_score() {
+    for (golden, input) in golden_inputs() {
+        assert_eq!(preflight::preflight(&input).unwrap(), golden.score, "{}", golden.file);
+    }
+}
+
+#[test]
+#[cfg_attr(stale_elf, ignore = "the embedded ELF doesn't match the guest sources")]
+fn guest_commits_golden_score() {
+    let client = ProverClient::mock();
+    for (golden, input) in golden_inputs() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&Task::Run(input.clone()));
+        let (public_values, _) = client.execute(ELF_FLAZKY_BIRD, stdin).run().unwrap();
+        let public_values = PublicValuesStruct::abi_decode(public_values.as_slice(), true).unwrap();
+        assert_eq!(public_values.kind, PublicValuesKind::Score as u8, "{}", golden.file);
+        assert_eq!(public_values.score, U256::from(golden.score), "{}", golden.file);
+        assert_eq!(public_values.nullifier, golden.nullifier, "{}", golden.file);
+        assert_eq!(public_values.player, input.player, "{}", golden.file);
+        assert_eq!(public_values.season, SEASON, "{}", golden.file);
+    }
+}
+
+#[test]
+#[cfg_attr(stale_elf, ignore = "the embedded ELF doesn't match the guest sources")]
+fn mock_proofs_match_golden_score() {
+    assert!(!mock_fixtures().is_empty());
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and the contracts built with forge"]
+async fn mock_proofs_mint_leaderboard_entries() {
+    let fixtures = mock_fixtures();
+
+    let anvil = Anvil::new().spawn();
+    let signer = PrivateKeySigner::from(anvil.keys()[0].clone());
+    let private_key = signer.to_bytes().to_string();
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(signer))
+        .on_http(anvil.endpoint_url());
+
+    let vkey: B256 = fixtures[0].1.vkey.parse().unwrap();
+    let address = deploy::deploy_with_mock_verifier(&provider, vkey, B256::ZERO).await.unwrap();
+    let contract = FlazkyBird::new(address, &provider);
+
+    // proofs the verifier can't check are rejected before sending anything
+    let mut compressed = fixtures[0].1.clone();
+    compressed.proof_type = ProofType::Compressed;
+    let err = submit::submit(&compressed, &anvil.endpoint(), address, &private_key).await.unwrap_err();
+    assert!(err.to_string().contains("can't be verified on-chain"), "{}", err);
+
+    // fixtures of another season are rejected before sending anything
+    assert!(submit::submit(&fixtures[0].1, &anvil.endpoint(), address, &private_key).await.is_err());
+    contract.setCurrentSeason(SEASON).send().await.unwrap().get_receipt().await.unwrap();
+
+    for (golden, fixture) in fixtures.iter() {
+        let token_id = submit::submit(fixture, &anvil.endpoint(), address, &private_key).await.unwrap();
+        let entry = contract.leaderboard(token_id).call().await.unwrap();
+        assert_eq!(entry.score, golden.score as u64, "{}", golden.file);
+        assert_eq!(entry.season, SEASON, "{}", golden.file);
+        assert_eq!(contract.ownerOf(token_id).call().await.unwrap()._0, PLAYER.parse::<Address>().unwrap());
+        assert!(contract.nullifierMap(golden.nullifier).call().await.unwrap()._0);
+    }
+
+    // the leader holds the best golden score
+    let best = fixtures.iter().map(|(golden, _)| golden.score).max().unwrap();
+    let leader = contract.leader(SEASON).call().await.unwrap()._0;
+    assert_eq!(contract.leaderboard(leader).call().await.unwrap().score, best as u64);
+    assert!(contract.leader(0).call().await.unwrap()._0.is_zero());
+    let standings = leaderboard::standings(&contract, SEASON).await.unwrap();
+    assert_eq!(standings.len(), fixtures.len());
+    assert!(standings.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+    // a fixture can't be submitted twice
+    assert!(submit::submit(&fixtures[0].1, &anvil.endpoint(), address, &private_key).await.is_err());
+}
@@ -0,0 +1,27 @@
+//! Loading fixtures generated by older versions of the host
+
+use std::fs;
+
+use prover_host::fixture::ProofFixture;
+
+#[test]
+fn fixtures_without_season_are_rejected() {
+    let path = std::env::temp_dir().join(format!("flazky-fixture-{}.json", std::process::id()));
+    let mut fixture = serde_json::json!({
+        "proof_type": "plonk",
+        "player": "0x00000000000000000000000000000000000f1a2b",
+        "score": "11",
+        "nullifier": format!("0x{}", "00".repeat(32)),
+        "public_values": "0x",
+        "proof": "0x",
+        "vkey": format!("0x{}", "00".repeat(32)),
+    });
+    fs::write(&path, fixture.to_string()).unwrap();
+    let err = ProofFixture::load(&path).unwrap_err();
+    assert!(err.to_string().contains("before seasons were committed"), "{}", err);
+
+    fixture["season"] = 2.into();
+    fs::write(&path, fixture.to_string()).unwrap();
+    assert_eq!(ProofFixture::load(&path).unwrap().season, 2);
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,36 @@
+//! Conversions of the golden traces of `logic/tests/traces` between the trace formats
+
+use std::fs;
+use std::path::PathBuf;
+
+use prover_host::formats::{self, TraceFormat};
+
+fn golden_traces() -> Vec<(PathBuf, Vec<u8>)> {
+    let traces_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../logic/tests/traces");
+    ["short.bin", "medium.bin", "long.bin"]
+        .into_iter()
+        .map(|file| (traces_dir.join(file), fs::read(traces_dir.join(file)).unwrap()))
+        .collect()
+}
+
+#[test]
+fn conversions_round_trip() {
+    for (path, encoded_trace) in golden_traces() {
+        let items = formats::decode(&encoded_trace, TraceFormat::Bincode).unwrap();
+        for format in [TraceFormat::Json, TraceFormat::Ndjson, TraceFormat::Compact] {
+            let converted = formats::encode(&items, format).unwrap();
+            assert_eq!(formats::decode(&converted, format).unwrap(), items, "{} as {:?}", path.display(), format);
+        }
+        assert_eq!(formats::encode(&items, TraceFormat::Bincode).unwrap(), encoded_trace, "{}", path.display());
+    }
+}
+
+#[test]
+fn trailing_bytes_are_rejected() {
+    for (path, mut encoded_trace) in golden_traces() {
+        // the guest accepts them, but they'd be lost converting the trace
+        encoded_trace.extend_from_slice(&[0xfb; 3]);
+        let err = formats::decode(&encoded_trace, TraceFormat::Bincode).unwrap_err();
+        assert!(err.to_string().contains("3 bytes after the last item"), "{}: {}", path.display(), err);
+    }
+}
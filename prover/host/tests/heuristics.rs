@@ -0,0 +1,83 @@
+//! Humanity scores of the golden traces of `logic/tests/traces` and of a scripted trace
+
+use std::fs;
+use std::path::PathBuf;
+
+use flazky_bird_lib::{Action, FlazkyBird, TraceItem};
+use prover_host::formats::{self, TraceFormat};
+use prover_host::heuristics;
+
+fn golden_trace(file: &str) -> Vec<u8> {
+    fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../logic/tests/traces").join(file)).unwrap()
+}
+
+/// A bot jumping every `jump_every` frames of exactly 1/60s, until it collides
+fn constant_cadence_trace(jump_every: usize) -> Vec<u8> {
+    let delta: f32 = 1. / 60.;
+    let mut game = FlazkyBird::new(true);
+    game.new_play(42);
+    let mut items = vec![TraceItem { action: Action::NewPlay, data: 42i32.to_le_bytes() }];
+    for frame in 0.. {
+        if frame % jump_every == 0 {
+            game.jump();
+            items.push(TraceItem { action: Action::Jump, data: [0; 4] });
+        }
+        items.push(TraceItem { action: Action::ApplyPhysics, data: delta.to_le_bytes() });
+        if game.apply_physics(delta) {
+            break;
+        }
+        items.push(TraceItem { action: Action::CheckCollisionAndMovePipes, data: delta.to_le_bytes() });
+        if game.check_collision_and_move_pipes(delta).0 {
+            break;
+        }
+    }
+    items.push(TraceItem { action: Action::GameOver, data: [0; 4] });
+    formats::encode(&items, TraceFormat::Bincode).unwrap()
+}
+
+#[test]
+fn golden_traces_are_scored() {
+    // the golden traces were generated by scripts, they only tell which signals are computed. The
+    // scores of real traces are what `--min-humanity` has to be calibrated on
+    let report = heuristics::analyze(&golden_trace("long.bin")).unwrap();
+    assert!(report.jump_interval_cv.is_some() && report.median_reaction_seconds.is_some());
+    assert!(report.score.is_some(), "{:?}", report);
+
+    // too few jumps for the cadence, the deltas still make a score
+    let report = heuristics::analyze(&golden_trace("medium.bin")).unwrap();
+    assert_eq!(report.jump_interval_cv, None);
+    assert!(report.delta_std_dev.is_some() && report.score.is_some(), "{:?}", report);
+
+    // the short trace was generated with constant deltas
+    let report = heuristics::analyze(&golden_trace("short.bin")).unwrap();
+    assert_eq!(report.score, Some(0.));
+    assert!(heuristics::check(&golden_trace("short.bin"), 0.5, true).is_err());
+}
+
+#[test]
+fn constant_cadence_is_rejected() {
+    let trace = constant_cadence_trace(16);
+    let report = heuristics::analyze(&trace).unwrap();
+    assert!(report.jump_interval_cv.unwrap() < 0.05, "{:?}", report);
+    assert!(report.delta_std_dev.unwrap() < 0.0002, "{:?}", report);
+    assert!(report.score.unwrap() < 0.5, "{:?}", report);
+    let err = heuristics::check(&trace, 0.5, true).unwrap_err();
+    assert!(err.to_string().contains("trace looks scripted"), "{}", err);
+}
+
+#[test]
+fn too_short_traces_are_unknown() {
+    let items = vec![
+        TraceItem { action: Action::NewPlay, data: 42i32.to_le_bytes() },
+        TraceItem { action: Action::Jump, data: [0; 4] },
+        TraceItem { action: Action::ApplyPhysics, data: (1f32 / 60.).to_le_bytes() },
+        TraceItem { action: Action::GameOver, data: [0; 4] },
+    ];
+    let trace = formats::encode(&items, TraceFormat::Bincode).unwrap();
+    let report = heuristics::analyze(&trace).unwrap();
+    assert_eq!(report.score, None);
+    // the caller decides what an unknown score means
+    let err = heuristics::check(&trace, 0.5, false).unwrap_err();
+    assert!(err.to_string().contains("unknown"), "{}", err);
+    assert_eq!(heuristics::check(&trace, 0.5, true).unwrap().score, None);
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use alloy_primitives::Address;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::db::{Database, RankedToken};
+
+pub type SharedDatabase = Arc<RwLock<Database>>;
+
+type ApiError = (StatusCode, String);
+
+const MAX_LIMIT: usize = 1000;
+
+#[derive(Debug, Serialize)]
+struct Status {
+    contract: Address,
+    last_block: Option<u64>,
+    tokens: usize,
+    seasons: Vec<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_limit() -> usize {
+    100
+}
+
+/// - `GET /status`: contract, last indexed block, number of tokens and seasons
+/// - `GET /seasons/{season}/leaderboard?offset=0&limit=100`: the standings of a season
+/// - `GET /tokens/{id}`: a token and its rank in its season
+/// - `GET /players/{address}/tokens`: the tokens of a player, in every season
+pub fn router(db: SharedDatabase) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/seasons/:season/leaderboard", get(leaderboard))
+        .route("/tokens/:id", get(token))
+        .route("/players/:address/tokens", get(player_tokens))
+        .with_state(db)
+}
+
+async fn status(State(db): State<SharedDatabase>) -> Json<Status> {
+    let db = db.read().await;
+    Json(Status {
+        contract: db.contract,
+        last_block: db.last_block,
+        tokens: db.tokens.len(),
+        seasons: db.seasons().into_iter().collect(),
+    })
+}
+
+async fn leaderboard(
+    State(db): State<SharedDatabase>,
+    Path(season): Path<u64>,
+    Query(page): Query<Page>,
+) -> Result<Json<Vec<RankedToken>>, ApiError> {
+    if page.limit > MAX_LIMIT {
+        return Err((StatusCode::BAD_REQUEST, format!("limit can't be higher than {}", MAX_LIMIT)));
+    }
+    let standings = db.read().await.standings(season);
+    Ok(Json(standings.into_iter().skip(page.offset).take(page.limit).collect()))
+}
+
+async fn token(State(db): State<SharedDatabase>, Path(id): Path<u64>) -> Result<Json<RankedToken>, ApiError> {
+    db.read().await.token(id).map(Json).ok_or((StatusCode::NOT_FOUND, format!("unknown token {}", id)))
+}
+
+async fn player_tokens(State(db): State<SharedDatabase>, Path(address): Path<Address>) -> Json<Vec<RankedToken>> {
+    Json(db.read().await.tokens_of(address))
+}
@@ -0,0 +1,110 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Token {
+    pub token_id: u64,
+    pub owner: Address,
+    pub score: u64,
+    pub season: u64,
+    /// Block the token was minted in
+    pub block: u64,
+}
+
+/// A token along with its position in the leaderboard of its season
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankedToken {
+    /// Starts at 1 for the leader
+    pub rank: u64,
+    #[serde(flatten)]
+    pub token: Token,
+}
+
+/// Tokens of a FlazkyBird deployment, as of `last_block`, stored as a JSON file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Database {
+    pub contract: Address,
+    /// Last block whose logs were indexed, `None` until the first sync
+    pub last_block: Option<u64>,
+    pub tokens: BTreeMap<u64, Token>,
+}
+
+impl Database {
+    pub fn new(contract: Address) -> Self {
+        Self { contract, last_block: None, tokens: BTreeMap::new() }
+    }
+
+    /// Loads the database at `path`, or starts an empty one if there's none yet
+    pub fn open(path: &Path, contract: Address) -> eyre::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(contract));
+        }
+        let db: Database = serde_json::from_slice(&fs::read(path)?)?;
+        eyre::ensure!(
+            db.contract == contract,
+            "{} indexes {}, not {}",
+            path.display(),
+            db.contract,
+            contract
+        );
+        Ok(db)
+    }
+
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // write and rename, so a crash never leaves a truncated database behind
+        let tmp_file = path.with_extension("tmp");
+        fs::write(&tmp_file, serde_json::to_string(self)?)?;
+        fs::rename(tmp_file, path)?;
+        Ok(())
+    }
+
+    pub fn mint(&mut self, token: Token) {
+        self.tokens.insert(token.token_id, token);
+    }
+
+    pub fn transfer(&mut self, token_id: u64, to: Address) -> eyre::Result<()> {
+        let token = self
+            .tokens
+            .get_mut(&token_id)
+            .ok_or_else(|| eyre::eyre!("transfer of token {} before it was minted", token_id))?;
+        token.owner = to;
+        Ok(())
+    }
+
+    pub fn seasons(&self) -> BTreeSet<u64> {
+        self.tokens.values().map(|token| token.season).collect()
+    }
+
+    /// The leaderboard of `season`. The contract inserts an entry after every entry with a higher
+    /// or equal score, so it's sorted by score, then by token ID
+    pub fn standings(&self, season: u64) -> Vec<RankedToken> {
+        let mut tokens = self.tokens.values().filter(|token| token.season == season).collect::<Vec<_>>();
+        tokens.sort_by(|a, b| b.score.cmp(&a.score).then(a.token_id.cmp(&b.token_id)));
+        tokens
+            .into_iter()
+            .enumerate()
+            .map(|(index, token)| RankedToken { rank: index as u64 + 1, token: token.clone() })
+            .collect()
+    }
+
+    pub fn token(&self, token_id: u64) -> Option<RankedToken> {
+        let season = self.tokens.get(&token_id)?.season;
+        self.standings(season).into_iter().find(|ranked| ranked.token.token_id == token_id)
+    }
+
+    /// Tokens owned by `owner`, in every season
+    pub fn tokens_of(&self, owner: Address) -> Vec<RankedToken> {
+        self.seasons()
+            .into_iter()
+            .flat_map(|season| self.standings(season))
+            .filter(|ranked| ranked.token.owner == owner)
+            .collect()
+    }
+}
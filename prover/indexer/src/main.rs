@@ -0,0 +1,89 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy::providers::ProviderBuilder;
+use alloy_primitives::Address;
+use clap::Parser;
+use prover_contract::FlazkyBird;
+use prover_indexer::api::{self, SharedDatabase};
+use prover_indexer::db::Database;
+use prover_indexer::sync;
+use tokio::sync::RwLock;
+
+#[derive(Parser, Debug)]
+struct Args {
+    #[clap(long, env = "SEPOLIA_RPC_URL")]
+    rpc_url: String,
+
+    /// Address of the FlazkyBird contract.
+    #[clap(long, env = "FLAZKY_BIRD_ADDRESS")]
+    contract: Address,
+
+    /// Block to start indexing from, the deployment block of the contract.
+    #[clap(long, default_value_t = 0)]
+    from_block: u64,
+
+    /// Blocks to stay behind the head of the chain, so reorgs don't end up in the database.
+    #[clap(long, default_value_t = 2)]
+    confirmations: u64,
+
+    /// Seconds between two syncs.
+    #[clap(long, default_value_t = 12)]
+    poll_interval: u64,
+
+    #[clap(long, default_value = "indexer.json")]
+    db: PathBuf,
+
+    #[clap(long, default_value = "127.0.0.1:3001")]
+    listen: SocketAddr,
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let db = Database::open(&args.db, args.contract)?;
+    println!("Loaded {} tokens, indexed up to block {:?}", db.tokens.len(), db.last_block);
+    let db: SharedDatabase = Arc::new(RwLock::new(db));
+    let listen = args.listen;
+    tokio::spawn(follow(args, db.clone()));
+
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    println!("Listening on {}", listen);
+    axum::serve(listener, api::router(db)).await?;
+    Ok(())
+}
+
+/// Syncs the database every `poll_interval` seconds. Each sync works on a copy, so the API keeps
+/// serving the previous state until the new one is saved
+async fn follow(args: Args, db: SharedDatabase) {
+    let provider = match args.rpc_url.parse() {
+        Ok(url) => ProviderBuilder::new().on_http(url),
+        Err(err) => {
+            println!("invalid RPC URL {}: {}", args.rpc_url, err);
+            return;
+        }
+    };
+    let contract = FlazkyBird::new(args.contract, provider);
+    let mut interval = tokio::time::interval(Duration::from_secs(args.poll_interval));
+    loop {
+        interval.tick().await;
+        let mut next = db.read().await.clone();
+        let result = match sync::sync(&contract, &mut next, args.from_block, args.confirmations).await {
+            Ok(transfers) => next.save(&args.db).map(|_| transfers),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(transfers) => {
+                if transfers > 0 {
+                    println!("Indexed {} transfers up to block {:?}", transfers, next.last_block);
+                }
+                *db.write().await = next;
+            }
+            Err(err) => println!("sync failed: {}", err),
+        }
+    }
+}
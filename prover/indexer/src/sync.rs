@@ -0,0 +1,49 @@
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use prover_contract::FlazkyBird;
+
+use crate::db::{Database, Token};
+
+/// Blocks queried per `eth_getLogs` call, most RPC providers cap the range
+const BLOCK_RANGE: u64 = 5_000;
+
+/// Indexes the `Transfer` logs from the last indexed block (or `start_block`) up to
+/// `confirmations` blocks behind the head, returning how many transfers were indexed.
+/// Mints read the score and season of the new token from the `leaderboard` mapping
+pub async fn sync<T, P>(
+    contract: &FlazkyBird::FlazkyBirdInstance<T, P>,
+    db: &mut Database,
+    start_block: u64,
+    confirmations: u64,
+) -> eyre::Result<usize>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let head = contract.provider().get_block_number().await?.saturating_sub(confirmations);
+    let mut from = db.last_block.map_or(start_block, |block| block + 1);
+    let mut transfers = 0;
+    while from <= head {
+        let to = (from + BLOCK_RANGE - 1).min(head);
+        let logs = contract.Transfer_filter().from_block(from).to_block(to).query().await?;
+        for (transfer, log) in logs {
+            let token_id: u64 = transfer.tokenId.try_into()?;
+            if transfer.from.is_zero() {
+                let entry = contract.leaderboard(transfer.tokenId).call().await?;
+                db.mint(Token {
+                    token_id,
+                    owner: transfer.to,
+                    score: entry.score,
+                    season: entry.season,
+                    block: log.block_number.unwrap_or(to),
+                });
+            } else {
+                db.transfer(token_id, transfer.to)?;
+            }
+            transfers += 1;
+        }
+        db.last_block = Some(to);
+        from = to + 1;
+    }
+    Ok(transfers)
+}
@@ -0,0 +1,119 @@
+//! Ranking of the indexed tokens and, with `--ignored`, indexing of `FlazkyBird.sol` deployed on anvil
+
+use std::fs;
+
+use alloy::network::EthereumWallet;
+use alloy::node_bindings::Anvil;
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::SolValue;
+use flazky_bird_lib::{PublicValuesKind, PublicValuesStruct};
+use prover_contract::{deploy, leaderboard, FlazkyBird};
+use prover_indexer::db::{Database, Token};
+use prover_indexer::sync;
+
+fn token(token_id: u64, score: u64, season: u64) -> Token {
+    Token { token_id, owner: Address::with_last_byte(token_id as u8), score, season, block: token_id }
+}
+
+#[test]
+fn standings_follow_the_contract_order() {
+    let mut db = Database::new(Address::ZERO);
+    for (token_id, score, season) in [(1, 100, 0), (2, 200, 0), (3, 200, 0), (4, 50, 1), (5, 300, 0)] {
+        db.mint(token(token_id, score, season));
+    }
+
+    // equal scores keep their insertion order
+    let standings = db.standings(0);
+    let ids = standings.iter().map(|ranked| ranked.token.token_id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![5, 2, 3, 1]);
+    assert_eq!(standings.iter().map(|ranked| ranked.rank).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+    // seasons are ranked apart
+    assert_eq!(db.token(4).unwrap().rank, 1);
+    assert_eq!(db.token(1).unwrap().rank, 4);
+    assert_eq!(db.seasons().into_iter().collect::<Vec<_>>(), vec![0, 1]);
+
+    db.transfer(4, Address::with_last_byte(1)).unwrap();
+    let owned = db.tokens_of(Address::with_last_byte(1));
+    assert_eq!(owned.iter().map(|ranked| ranked.token.token_id).collect::<Vec<_>>(), vec![1, 4]);
+    assert!(db.transfer(6, Address::ZERO).is_err());
+}
+
+#[test]
+fn database_round_trips() {
+    let path = std::env::temp_dir().join(format!("flazky-indexer-{}.json", std::process::id()));
+    let contract = Address::with_last_byte(0xfb);
+    let mut db = Database::new(contract);
+    db.mint(token(1, 10, 2));
+    db.last_block = Some(42);
+    db.save(&path).unwrap();
+
+    let loaded = Database::open(&path, contract).unwrap();
+    assert_eq!(loaded.last_block, Some(42));
+    assert_eq!(loaded.tokens, db.tokens);
+    // a database belongs to one deployment
+    assert!(Database::open(&path, Address::ZERO).is_err());
+    fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and the contracts built with forge"]
+async fn indexes_an_anvil_deployment() {
+    let anvil = Anvil::new().spawn();
+    let signer = PrivateKeySigner::from(anvil.keys()[0].clone());
+    let sender = signer.address();
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(signer))
+        .on_http(anvil.endpoint_url());
+
+    // the mock verifier accepts any proof, so entries only need public values
+    let address = deploy::deploy_with_mock_verifier(&provider, B256::ZERO, B256::ZERO).await.unwrap();
+    let contract = FlazkyBird::new(address, &provider);
+
+    let entries = [(120u64, 1u64), (40, 1), (300, 2), (120, 1), (75, 1)];
+    for (index, (score, season)) in entries.iter().enumerate() {
+        let public_values = PublicValuesStruct {
+            kind: PublicValuesKind::Score as u8,
+            player: sender,
+            season: *season,
+            score: U256::from(*score),
+            nullifier: B256::with_last_byte(index as u8 + 1),
+        };
+        // the deployer owns the contract and switches seasons between entries
+        contract.setCurrentSeason(*season).send().await.unwrap().get_receipt().await.unwrap();
+        let previous_token_id = leaderboard::previous_token_id(&contract, *season, *score).await.unwrap();
+        contract
+            .addLeaderboardEntry(public_values.abi_encode().into(), Bytes::new(), previous_token_id)
+            .send()
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+    }
+
+    let mut db = Database::new(address);
+    assert_eq!(sync::sync(&contract, &mut db, 0, 0).await.unwrap(), entries.len());
+    for season in [1, 2] {
+        let onchain = leaderboard::standings(&contract, season).await.unwrap();
+        let indexed = db.standings(season);
+        assert_eq!(indexed.len(), onchain.len());
+        for (indexed, onchain) in indexed.iter().zip(onchain.iter()) {
+            assert_eq!(indexed.rank, onchain.rank);
+            assert_eq!(U256::from(indexed.token.token_id), onchain.token_id);
+            assert_eq!(indexed.token.score, onchain.score);
+            assert_eq!(indexed.token.owner, onchain.owner);
+        }
+    }
+
+    // transfers after the last sync update the owner
+    let receiver = Address::with_last_byte(0x42);
+    contract.transferFrom(sender, receiver, U256::from(3)).send().await.unwrap().get_receipt().await.unwrap();
+    assert_eq!(sync::sync(&contract, &mut db, 0, 0).await.unwrap(), 1);
+    assert_eq!(db.token(3).unwrap().token.owner, receiver);
+    assert_eq!(db.last_block, Some(provider.get_block_number().await.unwrap()));
+    assert_eq!(db.tokens_of(receiver).len(), 1);
+}
@@ -0,0 +1,154 @@
+use std::fmt::Write;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+const BACKGROUND: &[u8] = include_bytes!("../../../docs/assets/sprites/background-day.png");
+const BASE: &[u8] = include_bytes!("../../../docs/assets/sprites/base.png");
+const BIRD: &[u8] = include_bytes!("../../../docs/assets/sprites/bluebird2.png");
+const NUMBERS: &[u8] = include_bytes!("../../../docs/assets/sprites/numbers.png");
+const PIPE: &[u8] = include_bytes!("../../../docs/assets/sprites/pipe.png");
+
+// Same size as the game window
+const WIDTH: u32 = 288;
+const HEIGHT: u32 = 512;
+
+// `numbers.png` stacks the digits 0 to 9 vertically
+const DIGIT_WIDTH: u32 = 24;
+const DIGIT_HEIGHT: u32 = 36;
+// Distance between two digits, as in the frontend
+const DIGIT_ADVANCE: u32 = 26;
+
+const DESCRIPTION: &str = "NFT obtained playing FlaZKy bird and proving the score on-chain";
+
+/// What a score card shows
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreCard {
+    pub score: u64,
+    /// Position in the leaderboard, starting at 1 for the leader
+    pub rank: u64,
+    pub seed: i32,
+    pub season: Option<u64>,
+}
+
+/// ERC-721 metadata, as returned by `tokenURI`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub name: String,
+    pub description: String,
+    /// The SVG card, as a base64 data URI
+    pub image: String,
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribute {
+    pub trait_type: String,
+    pub value: i64,
+}
+
+impl ScoreCard {
+    /// Renders the card with the sprites of the game: the score in large digits over the
+    /// background, the bird between two pipes, and the rank and seed on the ground
+    pub fn svg(&self) -> String {
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" style="image-rendering:pixelated">"#
+        )
+        .unwrap();
+        svg.push_str("<defs>");
+        image(&mut svg, "numbers", NUMBERS, DIGIT_WIDTH, DIGIT_HEIGHT * 10);
+        image(&mut svg, "pipe", PIPE, 52, 320);
+        image(&mut svg, "bird", BIRD, 102, 24);
+        svg.push_str("</defs>");
+
+        write!(svg, r#"<image width="{WIDTH}" height="{HEIGHT}" href="{}"/>"#, data_uri("image/png", BACKGROUND)).unwrap();
+        // lower pipe, and the upper one flipped
+        svg.push_str(r##"<use href="#pipe" x="196" y="300"/>"##);
+        svg.push_str(r##"<use href="#pipe" transform="translate(0 500) scale(1 -1)" x="196" y="330"/>"##);
+        // first frame of the bird, twice its size
+        svg.push_str(r##"<svg x="70" y="206" width="68" height="48" viewBox="0 0 34 24"><use href="#bird"/></svg>"##);
+        write!(svg, r#"<image y="400" width="336" height="112" href="{}"/>"#, data_uri("image/png", BASE)).unwrap();
+
+        number(&mut svg, self.score as i64, WIDTH / 2, 40, 1.);
+        label(&mut svg, "RANK", 72, 440);
+        number(&mut svg, self.rank as i64, 72, 452, 0.75);
+        label(&mut svg, "SEED", 216, 440);
+        number(&mut svg, self.seed as i64, 216, 452, 0.75);
+        if let Some(season) = self.season {
+            label(&mut svg, &format!("SEASON {}", season), WIDTH / 2, 500);
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+
+    pub fn metadata(&self, token_id: Option<u64>) -> Metadata {
+        let name = match token_id {
+            Some(token_id) => format!("FlaZKy Bird score #{}", token_id),
+            None => "FlaZKy Bird score".to_string(),
+        };
+        let mut attributes = vec![
+            Attribute { trait_type: "score".to_string(), value: self.score as i64 },
+            Attribute { trait_type: "rank".to_string(), value: self.rank as i64 },
+            Attribute { trait_type: "seed".to_string(), value: self.seed as i64 },
+        ];
+        if let Some(season) = self.season {
+            attributes.push(Attribute { trait_type: "season".to_string(), value: season as i64 });
+        }
+        Metadata {
+            name,
+            description: DESCRIPTION.to_string(),
+            image: data_uri("image/svg+xml", self.svg().as_bytes()),
+            attributes,
+        }
+    }
+}
+
+pub fn data_uri(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{};base64,{}", mime, BASE64.encode(bytes))
+}
+
+fn image(svg: &mut String, id: &str, png: &[u8], width: u32, height: u32) {
+    write!(svg, r#"<image id="{id}" width="{width}" height="{height}" href="{}"/>"#, data_uri("image/png", png)).unwrap();
+}
+
+/// Draws `value` with the digits of `numbers.png`, centered on `center_x`. The font has no minus
+/// sign, negative values get a bar in front of them
+fn number(svg: &mut String, value: i64, center_x: u32, y: u32, scale: f32) {
+    let digits = value.unsigned_abs().to_string();
+    let advance = DIGIT_ADVANCE as f32 * scale;
+    let (width, height) = (DIGIT_WIDTH as f32 * scale, DIGIT_HEIGHT as f32 * scale);
+    let glyphs = digits.len() + usize::from(value < 0);
+    let mut x = center_x as f32 - (advance * (glyphs - 1) as f32 + width) / 2.;
+    if value < 0 {
+        write!(
+            svg,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#fff" stroke="#000" stroke-width="2"/>"##,
+            x + width / 6.,
+            y as f32 + height * 0.4,
+            width * 2. / 3.,
+            height / 6.
+        )
+        .unwrap();
+        x += advance;
+    }
+    for digit in digits.bytes().map(|digit| (digit - b'0') as u32) {
+        write!(
+            svg,
+            r##"<svg x="{x}" y="{y}" width="{width}" height="{height}" viewBox="0 {} {DIGIT_WIDTH} {DIGIT_HEIGHT}"><use href="#numbers"/></svg>"##,
+            digit * DIGIT_HEIGHT
+        )
+        .unwrap();
+        x += advance;
+    }
+}
+
+fn label(svg: &mut String, text: &str, center_x: u32, y: u32) {
+    write!(
+        svg,
+        r##"<text x="{center_x}" y="{y}" text-anchor="middle" font-family="monospace" font-size="14" font-weight="bold" fill="#fff" stroke="#543847" stroke-width="3" paint-order="stroke">{text}</text>"##
+    )
+    .unwrap();
+}
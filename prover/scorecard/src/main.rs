@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Parser;
+use flazky_bird_lib::{replay_encoded, TraceDecoder};
+use prover_scorecard::ScoreCard;
+
+/// Render the score card of a token and print its metadata JSON.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Bincode encoded trace to read the score and the seed from.
+    #[clap(long, conflicts_with_all = ["score", "seed"])]
+    trace: Option<PathBuf>,
+
+    #[clap(long, required_unless_present = "trace")]
+    score: Option<u64>,
+
+    #[clap(long, required_unless_present = "trace", allow_negative_numbers = true)]
+    seed: Option<i32>,
+
+    /// Position in the leaderboard, starting at 1 for the leader.
+    #[clap(long)]
+    rank: u64,
+
+    #[clap(long)]
+    season: Option<u64>,
+
+    /// Token ID, added to the name of the token.
+    #[clap(long)]
+    token_id: Option<u64>,
+
+    /// Where to write the metadata, printed if not set.
+    #[clap(long)]
+    output: Option<PathBuf>,
+
+    /// Also write the card as an SVG file.
+    #[clap(long)]
+    svg: Option<PathBuf>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+    let (score, seed) = match &args.trace {
+        Some(trace) => {
+            let encoded_trace = fs::read(trace)?;
+            let score = replay_encoded(&encoded_trace, |_| {})?.score();
+            // the replay checked the trace starts with `NewPlay`
+            let first = TraceDecoder::new(&encoded_trace)?.next().ok_or_else(|| eyre::eyre!("empty trace"))??;
+            (score.into(), i32::from_le_bytes(first.data))
+        }
+        None => (args.score.unwrap_or_default(), args.seed.unwrap_or_default()),
+    };
+    let card = ScoreCard { score, rank: args.rank, seed, season: args.season };
+
+    if let Some(path) = &args.svg {
+        fs::write(path, card.svg())?;
+    }
+    let metadata = serde_json::to_string_pretty(&card.metadata(args.token_id))?;
+    match &args.output {
+        Some(path) => fs::write(path, metadata)?,
+        None => println!("{}", metadata),
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//! Rendering of the score cards and their metadata
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use prover_scorecard::ScoreCard;
+
+/// viewBox of a digit of `numbers.png`
+fn digit(digit: u32) -> String {
+    format!(r#"viewBox="0 {} 24 36""#, digit * 36)
+}
+
+#[test]
+fn card_draws_every_digit() {
+    let card = ScoreCard { score: 1097, rank: 3, seed: -999, season: None };
+    let svg = card.svg();
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+    // score, rank and seed
+    assert_eq!(svg.matches("<use href=\"#numbers\"/>").count(), 4 + 1 + 3);
+    for value in [0, 1, 3, 7, 9] {
+        assert!(svg.contains(&digit(value)), "digit {} is missing", value);
+    }
+    // the minus sign of the seed
+    assert_eq!(svg.matches("<rect").count(), 1);
+    assert!(!svg.contains("SEASON"));
+}
+
+#[test]
+fn metadata_embeds_the_card() {
+    let card = ScoreCard { score: 11, rank: 1, seed: 12345, season: Some(2) };
+    let metadata = card.metadata(Some(7));
+    assert_eq!(metadata.name, "FlaZKy Bird score #7");
+
+    let attributes =
+        metadata.attributes.iter().map(|attribute| (attribute.trait_type.as_str(), attribute.value)).collect::<Vec<_>>();
+    assert_eq!(attributes, vec![("score", 11), ("rank", 1), ("seed", 12345), ("season", 2)]);
+
+    let image = metadata.image.strip_prefix("data:image/svg+xml;base64,").unwrap();
+    assert_eq!(String::from_utf8(BASE64.decode(image).unwrap()).unwrap(), card.svg());
+
+    let json: serde_json::Value = serde_json::to_value(&metadata).unwrap();
+    assert_eq!(json["attributes"][0]["trait_type"], "score");
+    assert_eq!(json["attributes"][0]["value"], 11);
+}
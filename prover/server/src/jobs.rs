@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Proving,
+    Done,
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub player: Address,
+    /// Jobs created before seasons were committed are for season 0
+    #[serde(default)]
+    pub season: u64,
+    /// Score of the native replay, checked again once the proof is generated
+    pub score: u32,
+    pub created_at: u64,
+    #[serde(flatten)]
+    pub status: JobStatus,
+}
+
+/// Jobs persisted on disk, one directory per job with its trace, its state (`job.json`) and,
+/// once proven, its fixture
+pub struct JobStore {
+    dir: PathBuf,
+    jobs: Mutex<HashMap<String, Job>>,
+}
+
+impl JobStore {
+    pub fn open(dir: PathBuf) -> eyre::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let mut jobs = HashMap::new();
+        for entry in fs::read_dir(&dir)? {
+            let job_file = entry?.path().join("job.json");
+            if job_file.is_file() {
+                let job: Job = serde_json::from_slice(&fs::read(job_file)?)?;
+                jobs.insert(job.id.clone(), job);
+            }
+        }
+        Ok(Self { dir, jobs: Mutex::new(jobs) })
+    }
+
+    pub fn create(&self, player: Address, season: u64, trace: &[u8], score: u32) -> eyre::Result<Job> {
+        let id = format!("{:016x}", rand::random::<u64>());
+        let job = Job {
+            id: id.clone(),
+            player,
+            season,
+            score,
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            status: JobStatus::Queued,
+        };
+        fs::create_dir_all(self.dir.join(&id))?;
+        fs::write(self.trace_path(&id), trace)?;
+        self.save(&job)?;
+        self.jobs.lock().unwrap().insert(id, job.clone());
+        Ok(job)
+    }
+
+    pub fn get(&self, id: &str) -> Option<Job> {
+        self.jobs.lock().unwrap().get(id).cloned()
+    }
+
+    pub fn set_status(&self, id: &str, status: JobStatus) -> eyre::Result<()> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.get_mut(id).ok_or_else(|| eyre::eyre!("unknown job {}", id))?;
+        job.status = status;
+        self.save(job)
+    }
+
+    /// Jobs that still have to be proven, oldest first. Jobs that were being proven when the
+    /// server stopped are proven again
+    pub fn pending(&self) -> Vec<String> {
+        let jobs = self.jobs.lock().unwrap();
+        let mut pending = jobs
+            .values()
+            .filter(|job| matches!(job.status, JobStatus::Queued | JobStatus::Proving))
+            .collect::<Vec<_>>();
+        pending.sort_by_key(|job| job.created_at);
+        pending.into_iter().map(|job| job.id.clone()).collect()
+    }
+
+    pub fn trace_path(&self, id: &str) -> PathBuf {
+        self.dir.join(id).join("trace.bin")
+    }
+
+    pub fn fixture_path(&self, id: &str) -> PathBuf {
+        self.dir.join(id).join("fixture.json")
+    }
+
+    fn save(&self, job: &Job) -> eyre::Result<()> {
+        // write and rename, so a crash never leaves a truncated job file behind
+        let job_file = self.dir.join(&job.id).join("job.json");
+        let tmp_file = job_file.with_extension("json.tmp");
+        fs::write(&tmp_file, serde_json::to_string_pretty(job)?)?;
+        fs::rename(tmp_file, job_file)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use std::fs;
+use std::sync::Arc;
+
+use alloy_primitives::Address;
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use flazky_bird_lib::{Claim, Input, Task};
+use prover_host::fixture::{ProofFixture, ProofType};
+use prover_host::{heuristics, preflight};
+use serde::Deserialize;
+use sp1_sdk::{ProverClient, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+use tokio::sync::mpsc;
+
+pub mod jobs;
+
+use jobs::{Job, JobStatus, JobStore};
+
+pub struct Prover {
+    pub client: ProverClient,
+    pub pk: SP1ProvingKey,
+    pub vk: SP1VerifyingKey,
+    pub proof_type: ProofType,
+}
+
+pub struct Options {
+    /// Largest trace accepted, in bytes
+    pub max_trace_size: usize,
+    /// Reject traces whose humanity score is lower than this
+    pub min_humanity: Option<f32>,
+    /// Accept traces too short to be scored, when `min_humanity` is set
+    pub allow_unknown_humanity: bool,
+}
+
+#[derive(Clone)]
+struct AppState {
+    store: Arc<JobStore>,
+    queue: mpsc::UnboundedSender<String>,
+    min_humanity: Option<f32>,
+    allow_unknown_humanity: bool,
+}
+
+#[derive(Deserialize)]
+struct NewJob {
+    player: Address,
+    #[serde(default)]
+    season: u64,
+}
+
+type ApiError = (StatusCode, String);
+
+/// Queues the jobs left from a previous run, starts the worker proving them and returns the API:
+///
+/// - `POST /jobs?player=0x...&season=N` with the encoded trace as body: queues a job
+/// - `GET /jobs/{id}`: the job and its status
+/// - `GET /jobs/{id}/fixture`: the fixture of a job that is done
+pub fn start(store: Arc<JobStore>, prover: Arc<Prover>, options: Options) -> eyre::Result<Router> {
+    // queue the jobs left from a previous run before accepting new ones
+    let (queue, jobs) = mpsc::unbounded_channel();
+    for id in store.pending() {
+        queue.send(id)?;
+    }
+    tokio::spawn(worker(store.clone(), prover, jobs));
+
+    Ok(Router::new()
+        .route("/jobs", post(create_job))
+        .route("/jobs/:id", get(job_status))
+        .route("/jobs/:id/fixture", get(job_fixture))
+        .layer(DefaultBodyLimit::max(options.max_trace_size))
+        .with_state(AppState {
+            store,
+            queue,
+            min_humanity: options.min_humanity,
+            allow_unknown_humanity: options.allow_unknown_humanity,
+        }))
+}
+
+async fn create_job(
+    State(state): State<AppState>,
+    Query(new_job): Query<NewJob>,
+    trace: Bytes,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    // replaying the trace and writing it take a while for long traces, keep them off the runtime
+    let store = state.store.clone();
+    let (min_humanity, allow_unknown_humanity) = (state.min_humanity, state.allow_unknown_humanity);
+    let job = tokio::task::spawn_blocking(move || {
+        let input = input(trace.to_vec(), new_job.player, new_job.season);
+        // reject invalid traces right away, instead of failing in the queue
+        let score = preflight::preflight(&input).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+        if let Some(min_humanity) = min_humanity {
+            heuristics::check(&input.encoded_trace, min_humanity, allow_unknown_humanity)
+                .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()))?;
+        }
+        store.create(new_job.player, new_job.season, &trace, score).map_err(internal_error)
+    })
+    .await
+    .map_err(internal_error)??;
+    state.queue.send(job.id.clone()).map_err(internal_error)?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+async fn job_status(State(state): State<AppState>, Path(id): Path<String>) -> Result<Json<Job>, ApiError> {
+    state.store.get(&id).map(Json).ok_or((StatusCode::NOT_FOUND, format!("unknown job {}", id)))
+}
+
+async fn job_fixture(State(state): State<AppState>, Path(id): Path<String>) -> Result<Json<ProofFixture>, ApiError> {
+    let job = state.store.get(&id).ok_or((StatusCode::NOT_FOUND, format!("unknown job {}", id)))?;
+    if job.status != JobStatus::Done {
+        return Err((StatusCode::CONFLICT, format!("job {} is not done", id)));
+    }
+    let fixture_path = state.store.fixture_path(&id);
+    tokio::task::spawn_blocking(move || ProofFixture::load(&fixture_path))
+        .await
+        .map_err(internal_error)?
+        .map(Json)
+        .map_err(internal_error)
+}
+
+fn internal_error(err: impl ToString) -> ApiError {
+    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+}
+
+fn input(encoded_trace: Vec<u8>, player: Address, season: u64) -> Input {
+    Input {
+        encoded_trace,
+        player,
+        season,
+        claim: Claim::Score,
+        salt: None,
+    }
+}
+
+/// Proves the queued jobs one at a time
+async fn worker(store: Arc<JobStore>, prover: Arc<Prover>, mut jobs: mpsc::UnboundedReceiver<String>) {
+    while let Some(id) = jobs.recv().await {
+        if let Err(err) = store.set_status(&id, JobStatus::Proving) {
+            println!("job {}: {}", id, err);
+            continue;
+        }
+        let (job_store, job_prover, job_id) = (store.clone(), prover.clone(), id.clone());
+        let result = tokio::task::spawn_blocking(move || prove_job(&job_store, &job_prover, &job_id)).await;
+        let status = match result {
+            Ok(Ok(())) => JobStatus::Done,
+            Ok(Err(err)) => JobStatus::Failed { error: err.to_string() },
+            Err(err) => JobStatus::Failed { error: err.to_string() },
+        };
+        println!("job {}: {:?}", id, status);
+        if let Err(err) = store.set_status(&id, status) {
+            println!("job {}: {}", id, err);
+        }
+    }
+}
+
+fn prove_job(store: &JobStore, prover: &Prover, id: &str) -> eyre::Result<()> {
+    let job = store.get(id).ok_or_else(|| eyre::eyre!("unknown job {}", id))?;
+    let input = input(fs::read(store.trace_path(id))?, job.player, job.season);
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&Task::Run(input.clone()));
+    let proof = prover.proof_type.prove(&prover.client, &prover.pk, stdin)?;
+    preflight::check_committed_score(&input, job.score, proof.public_values.as_slice())?;
+    let fixture = ProofFixture::new(&proof, prover.proof_type, &prover.vk, &input);
+    fixture.save(&store.fixture_path(id), &proof, &input)
+}
@@ -0,0 +1,61 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::Parser;
+use prover_host::fixture::ProofType;
+use prover_host::{keys, ELF_FLAZKY_BIRD};
+use prover_server::jobs::JobStore;
+use prover_server::{Options, Prover};
+use sp1_sdk::{utils, ProverClient};
+
+#[derive(Parser, Debug)]
+struct Args {
+    #[clap(long, default_value = "127.0.0.1:3000")]
+    listen: SocketAddr,
+
+    /// Where jobs, traces and fixtures are stored.
+    #[clap(long, default_value = "jobs")]
+    data_dir: PathBuf,
+
+    #[clap(long, value_enum, default_value_t = ProofType::Plonk)]
+    proof_type: ProofType,
+
+    /// Largest trace accepted, in bytes.
+    #[clap(long, default_value_t = 64 * 1024 * 1024)]
+    max_trace_size: usize,
+
+    /// Reject traces whose humanity score (from 0, scripted, to 1, human) is lower than this.
+    #[clap(long)]
+    min_humanity: Option<f32>,
+
+    /// With `--min-humanity`, still accept traces too short to be scored.
+    #[clap(long, requires = "min_humanity")]
+    allow_unknown_humanity: bool,
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    dotenv::dotenv().ok();
+    utils::setup_logger();
+    let args = Args::parse();
+
+    let store = Arc::new(JobStore::open(args.data_dir)?);
+    let client = ProverClient::new();
+    let (pk, vk) = keys::setup(&client, ELF_FLAZKY_BIRD);
+    let prover = Arc::new(Prover { client, pk, vk, proof_type: args.proof_type });
+
+    let app = prover_server::start(
+        store,
+        prover,
+        Options {
+            max_trace_size: args.max_trace_size,
+            min_humanity: args.min_humanity,
+            allow_unknown_humanity: args.allow_unknown_humanity,
+        },
+    )?;
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    println!("Listening on {}", args.listen);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
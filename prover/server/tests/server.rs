@@ -0,0 +1,149 @@
+//! The proving service with the mock prover: jobs are created, proven and served, and the ones left
+//! from a previous run are proven again when the service restarts
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy_primitives::Address;
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use prover_host::fixture::{ProofFixture, ProofType};
+use prover_host::ELF_FLAZKY_BIRD;
+use prover_server::jobs::{Job, JobStatus, JobStore};
+use prover_server::{Options, Prover};
+use sp1_sdk::ProverClient;
+use tower::ServiceExt;
+
+const PLAYER: &str = "0x00000000000000000000000000000000000f1a2b";
+// `short.bin` of the golden traces
+const SCORE: u32 = 1;
+
+fn trace() -> Vec<u8> {
+    fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../logic/tests/traces/short.bin")).unwrap()
+}
+
+fn data_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("flazky-server-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// The mock prover still executes the guest, whose public values don't match the native replay
+/// when the build used a stale ELF (`FLAZKY_ALLOW_STALE_ELF`)
+fn skip_stale_elf() -> bool {
+    if prover_host::STALE_ELF {
+        println!("skipped: the embedded ELF doesn't match the guest sources");
+    }
+    prover_host::STALE_ELF
+}
+
+fn app(store: Arc<JobStore>) -> Router {
+    let client = ProverClient::mock();
+    let (pk, vk) = client.setup(ELF_FLAZKY_BIRD);
+    let prover = Arc::new(Prover { client, pk, vk, proof_type: ProofType::Plonk });
+    let options = Options { max_trace_size: 1024 * 1024, min_humanity: None, allow_unknown_humanity: false };
+    prover_server::start(store, prover, options).unwrap()
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Vec<u8>) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    (status, to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec())
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    send(app, Request::get(uri).body(Body::empty()).unwrap()).await
+}
+
+async fn create(app: &Router, trace: Vec<u8>) -> (StatusCode, Vec<u8>) {
+    let uri = format!("/jobs?player={}&season=2", PLAYER);
+    send(app, Request::post(uri).body(Body::from(trace)).unwrap()).await
+}
+
+/// Polls the job until it isn't queued nor being proven anymore
+async fn wait_for(app: &Router, id: &str) -> Job {
+    for _ in 0..600 {
+        let (status, body) = get(app, &format!("/jobs/{}", id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let job: Job = serde_json::from_slice(&body).unwrap();
+        if !matches!(job.status, JobStatus::Queued | JobStatus::Proving) {
+            return job;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    panic!("job {} is still pending", id);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn proves_submitted_traces() {
+    if skip_stale_elf() {
+        return;
+    }
+    let dir = data_dir("prove");
+    let app = app(Arc::new(JobStore::open(dir.clone()).unwrap()));
+
+    let (status, body) = create(&app, trace()).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let job: Job = serde_json::from_slice(&body).unwrap();
+    assert_eq!(job.score, SCORE);
+    assert_eq!(job.season, 2);
+    assert_eq!(job.player, PLAYER.parse::<Address>().unwrap());
+
+    assert_eq!(wait_for(&app, &job.id).await.status, JobStatus::Done);
+    let (status, body) = get(&app, &format!("/jobs/{}/fixture", job.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let fixture: ProofFixture = serde_json::from_slice(&body).unwrap();
+    assert_eq!(fixture.score, Some(SCORE.to_string()));
+    assert_eq!(fixture.season, 2);
+    assert_eq!(fixture.player.parse::<Address>().unwrap(), job.player);
+
+    assert_eq!(get(&app, "/jobs/0000000000000000").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get(&app, "/jobs/0000000000000000/fixture").await.0, StatusCode::NOT_FOUND);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_invalid_traces() {
+    let dir = data_dir("invalid");
+    let app = app(Arc::new(JobStore::open(dir.clone()).unwrap()));
+
+    let trace = trace();
+    let (status, _) = create(&app, trace[..trace.len() - 8].to_vec()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, Request::post("/jobs?player=0x1234").body(Body::from(trace)).unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // no job was stored for them
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requeues_unfinished_jobs_on_restart() {
+    if skip_stale_elf() {
+        return;
+    }
+    let dir = data_dir("restart");
+    let player = PLAYER.parse().unwrap();
+    let (interrupted, failed) = {
+        // jobs as a stopped service left them
+        let store = JobStore::open(dir.clone()).unwrap();
+        let interrupted = store.create(player, 0, &trace(), SCORE).unwrap();
+        store.set_status(&interrupted.id, JobStatus::Proving).unwrap();
+        let failed = store.create(player, 0, &trace(), SCORE).unwrap();
+        store.set_status(&failed.id, JobStatus::Failed { error: "out of memory".to_string() }).unwrap();
+        (interrupted, failed)
+    };
+
+    let app = app(Arc::new(JobStore::open(dir.clone()).unwrap()));
+    assert_eq!(wait_for(&app, &interrupted.id).await.status, JobStatus::Done);
+    assert_eq!(get(&app, &format!("/jobs/{}/fixture", interrupted.id)).await.0, StatusCode::OK);
+
+    // failed jobs aren't proven again, and have no fixture
+    let job = wait_for(&app, &failed.id).await;
+    assert_eq!(job.status, JobStatus::Failed { error: "out of memory".to_string() });
+    assert_eq!(get(&app, &format!("/jobs/{}/fixture", failed.id)).await.0, StatusCode::CONFLICT);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -12,7 +12,7 @@ contract DeployMock is Script {
         vm.startBroadcast(deployerPrivateKey);
 
         SP1VerifierMock mockVerifier = new SP1VerifierMock();
-        FlazkyBird flazkyBird = new FlazkyBird(mockVerifier, bytes32(type(uint256).max));
+        FlazkyBird flazkyBird = new FlazkyBird(mockVerifier, bytes32(type(uint256).max), bytes32(type(uint256).max));
 
         vm.stopBroadcast();
     }
@@ -23,9 +23,13 @@ contract DeploySepolia is Script {
         uint256 deployerPrivateKey = vm.envUint("PRIVATE_KEY");
         vm.startBroadcast(deployerPrivateKey);
 
-        bytes32 vkey = bytes32(0x00d46e47dc204c9d22a872106269c07fab0001c502ff00a9c1480e4b056a89c5);
+        // verifying keys of the ELF being deployed, printed by `prover-host vkey`
+        bytes32 vkey = vm.envBytes32("VKEY");
         ISP1Verifier sp1Gateway = ISP1Verifier(0x3B6041173B80E77f038f3F2C0f9744f04837185e);
-        FlazkyBird flazkyBird = new FlazkyBird(sp1Gateway, vkey);
+        bytes32 segmentVKey = vm.envBytes32("SEGMENT_VKEY");
+        FlazkyBird flazkyBird = new FlazkyBird(sp1Gateway, vkey, segmentVKey);
+        // the deployer owns the contract and starts the next seasons with `setCurrentSeason`
+        flazkyBird.setCurrentSeason(uint64(vm.envOr("SEASON", uint256(0))));
 
         vm.stopBroadcast();
     }
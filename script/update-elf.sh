@@ -0,0 +1,28 @@
+#!/usr/bin/env bash
+# Rebuilds the guest in the SP1 docker image, checks in the ELF with the hashes of its sources, pins
+# its verifying key and regenerates the contract fixtures. Needs the SP1 toolchain and docker. Run it
+# after changing prover/client or logic, and commit elf/, prover/host/src/lib.rs and
+# contracts/fixtures together.
+set -euo pipefail
+
+root="$(cd "$(dirname "$0")/.." && pwd)"
+cd "$root/prover/host"
+
+# writes elf/flazky-bird and elf/flazky-bird.sources
+FLAZKY_ELF_DOCKER=1 FLAZKY_UPDATE_ELF=1 cargo build --release
+
+# from now on the checked-in ELF is embedded, a local build could differ from the docker one
+export FLAZKY_SKIP_ELF_BUILD=1
+vkey="$(cargo run --release --quiet -- vkey | head -n 1)"
+if [[ ! "$vkey" =~ ^0x[0-9a-f]{64}$ ]]; then
+    echo "unexpected vkey: $vkey" >&2
+    exit 1
+fi
+sed -i.bak -E "s/^(pub const PINNED_VKEY: &str = )\"0x[0-9a-f]{64}\";/\1\"$vkey\";/" src/lib.rs
+rm src/lib.rs.bak
+
+cargo run --release --quiet -- check-vkey
+
+# the contract tests use the mock verifier, mock proofs are enough for their public values and vkey
+SP1_PROVER=mock cargo run --release --quiet -- contract-fixtures --traces '../../logic/tests/traces/*.bin'
+echo "elf/flazky-bird rebuilt, PINNED_VKEY is now $vkey"